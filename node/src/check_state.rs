@@ -0,0 +1,65 @@
+//! The `check-state` subcommand, which runs the DAO's invariant checks against the local database.
+
+use node_template_runtime::{opaque::Block, BlockNumber, RuntimeApi};
+use pallet_template_runtime_api::DaoApi;
+use sc_cli::{CliConfiguration, PruningParams, SharedParams};
+use sc_service::Configuration;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::generic::BlockId;
+use structopt::StructOpt;
+
+/// The `check-state` command used to verify the DAO's storage invariants at a block.
+#[derive(Debug, StructOpt)]
+pub struct CheckStateCmd {
+	/// Number of the block whose state to check. Defaults to the best block.
+	#[structopt(long, value_name = "NUMBER")]
+	pub at: Option<BlockNumber>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+}
+
+impl CheckStateCmd {
+	/// Run the invariant checks, failing with the first violated invariant.
+	pub fn run(&self, config: Configuration) -> sc_cli::Result<()> {
+		let builder = sc_service::ServiceBuilder::new_full::<
+			Block,
+			RuntimeApi,
+			crate::service::Executor,
+		>(config)?;
+		let client = builder.client();
+
+		let at = match self.at {
+			Some(number) => BlockId::Number(number),
+			None => BlockId::Hash(client.info().best_hash),
+		};
+
+		client.runtime_api()
+			.try_state(&at)
+			.map_err(|e| format!("Failed to call DaoApi::try_state at {}: {:?}", at, e))?
+			.map_err(|e| format!(
+				"DAO invariant violated at {}: {}",
+				at,
+				String::from_utf8_lossy(&e),
+			))?;
+
+		println!("DAO invariants hold at {}", at);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for CheckStateCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+}
@@ -1,5 +1,6 @@
-use sc_cli::{RunCmd, Subcommand};
+use sc_cli::RunCmd;
 use structopt::StructOpt;
+use crate::check_state::CheckStateCmd;
 
 #[derive(Debug, StructOpt)]
 pub struct Cli {
@@ -8,4 +9,50 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// Seal blocks on demand instead of running Aura and GRANDPA.
+	///
+	/// `instant` seals a block for every transaction entering the pool, `manual` only seals
+	/// when `engine_createBlock` is called over RPC and a number seals a block every that many
+	/// milliseconds. Intended for development chains only.
+	#[structopt(long, value_name = "instant|manual|MILLISECONDS")]
+	pub sealing: Option<Sealing>,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Subcommand {
+	#[structopt(flatten)]
+	Base(sc_cli::Subcommand),
+
+	/// Check the DAO's invariants against the state of a block in the local database.
+	CheckState(CheckStateCmd),
+}
+
+/// How blocks are sealed when the node runs without Aura and GRANDPA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sealing {
+	/// Seal a block as soon as a transaction is imported into the pool.
+	Instant,
+	/// Seal blocks only when requested through the `engine_createBlock` RPC.
+	Manual,
+	/// Seal a block every given number of milliseconds, in addition to RPC requests.
+	Interval(u64),
+}
+
+impl std::str::FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s {
+			"instant" => Sealing::Instant,
+			"manual" => Sealing::Manual,
+			millis => match millis.parse::<u64>() {
+				Ok(millis) if millis > 0 => Sealing::Interval(millis),
+				_ => return Err(format!(
+					"invalid sealing `{}`, expected `instant`, `manual` or a block time in milliseconds",
+					millis,
+				)),
+			},
+		})
+	}
 }
@@ -16,7 +16,7 @@
 // limitations under the License.
 
 use crate::chain_spec;
-use crate::cli::Cli;
+use crate::cli::{Cli, Subcommand};
 use crate::service;
 use sc_cli::SubstrateCli;
 
@@ -65,17 +65,31 @@ pub fn run() -> sc_cli::Result<()> {
 	let cli = Cli::from_args();
 
 	match &cli.subcommand {
-		Some(subcommand) => {
+		Some(Subcommand::CheckState(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| cmd.run(config))
+		}
+		Some(Subcommand::Base(subcommand)) => {
 			let runner = cli.create_runner(subcommand)?;
-			runner.run_subcommand(subcommand, |config| Ok(new_full_start!(config).0))
+			match cli.sealing {
+				Some(_) => runner.run_subcommand(subcommand, |config| Ok(new_manual_seal_start!(config).0)),
+				None => runner.run_subcommand(subcommand, |config| Ok(new_full_start!(config).0)),
+			}
 		}
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
-			runner.run_node(
-				service::new_light,
-				service::new_full,
-				node_template_runtime::VERSION
-			)
+			match cli.sealing {
+				Some(sealing) => runner.run_node(
+					service::new_light,
+					move |config| service::new_full_manual_seal(config, sealing),
+					node_template_runtime::VERSION
+				),
+				None => runner.run_node(
+					service::new_light,
+					service::new_full,
+					node_template_runtime::VERSION
+				),
+			}
 		}
 	}
 }
@@ -1,18 +1,28 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
+use std::convert::TryInto;
 use std::sync::Arc;
-use std::time::Duration;
-use sc_client_api::ExecutorProvider;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use futures::{SinkExt, StreamExt};
+use sc_client_api::{ExecutorProvider, StorageProvider};
 use sc_consensus::LongestChain;
 use node_template_runtime::{self, opaque::Block, RuntimeApi};
-use sc_service::{error::{Error as ServiceError}, AbstractService, Configuration, ServiceBuilder};
-use sp_inherents::InherentDataProviders;
+use sc_service::{
+	error::{Error as ServiceError}, AbstractService, Configuration, ServiceBuilder, TFullBackend,
+};
+use sp_blockchain::HeaderBackend;
+use sp_core::{storage::StorageKey, twox_128};
+use sp_runtime::generic::BlockId;
+use sp_inherents::{InherentData, InherentDataProviders, InherentIdentifier, ProvideInherentData};
 use sc_executor::native_executor_instance;
 pub use sc_executor::NativeExecutor;
 use sp_consensus_aura::sr25519::{AuthorityPair as AuraPair};
 use sc_finality_grandpa::{
 	FinalityProofProvider as GrandpaFinalityProofProvider, StorageAndProofProvider, SharedVoterState,
 };
+use sc_consensus_manual_seal::{rpc::{ManualSeal, ManualSealApi}, EngineCommand};
+use crate::cli::Sealing;
 
 // Our native executor instance.
 native_executor_instance!(
@@ -92,6 +102,109 @@ macro_rules! new_full_start {
 	}}
 }
 
+/// Starts a `ServiceBuilder` for a full service that seals blocks manually.
+///
+/// Same as `new_full_start!`, except that blocks are imported without Aura or GRANDPA
+/// checks and timestamps are provided by `ManualSealTimestamp`.
+macro_rules! new_manual_seal_start {
+	($config:expr) => {{
+		let inherent_data_providers = sp_inherents::InherentDataProviders::new();
+
+		let builder = sc_service::ServiceBuilder::new_full::<
+			node_template_runtime::opaque::Block,
+			node_template_runtime::RuntimeApi,
+			crate::service::Executor
+		>($config)?
+			.with_select_chain(|_config, backend| {
+				Ok(sc_consensus::LongestChain::new(backend.clone()))
+			})?
+			.with_transaction_pool(|builder| {
+				let pool_api = sc_transaction_pool::FullChainApi::new(
+					builder.client().clone(),
+				);
+				Ok(sc_transaction_pool::BasicPool::new(
+					builder.config().transaction_pool.clone(),
+					std::sync::Arc::new(pool_api),
+					builder.prometheus_registry(),
+				))
+			})?
+			.with_import_queue(|
+				_config,
+				client,
+				_select_chain,
+				_transaction_pool,
+				spawn_task_handle,
+				registry,
+			| {
+				Ok(sc_consensus_manual_seal::import_queue(
+					Box::new(client),
+					spawn_task_handle,
+					registry,
+				))
+			})?;
+
+		let last_timestamp = crate::service::best_block_timestamp(&**builder.client())?;
+		inherent_data_providers
+			.register_provider(crate::service::ManualSealTimestamp::new(last_timestamp))
+			.map_err(Into::into)
+			.map_err(sp_consensus::Error::InherentData)?;
+
+		(builder, inherent_data_providers)
+	}}
+}
+
+/// Timestamp inherent data provider for manually sealed chains.
+///
+/// Every block is stamped exactly one slot after its predecessor, so that the runtime's
+/// `MinimumPeriod` and Aura slot checks hold no matter how quickly blocks are sealed. Sealing
+/// many blocks in a row therefore fast-forwards the chain's notion of time.
+pub struct ManualSealTimestamp {
+	next: AtomicU64,
+}
+
+impl ManualSealTimestamp {
+	/// Create a provider whose first timestamp is one slot after `last`, the timestamp of the
+	/// best block, or the current time if that is later. Timestamps thus keep increasing when a
+	/// node restarts on a chain it fast-forwarded past the wall clock.
+	pub fn new(last: u64) -> Self {
+		let now = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.expect("Current time is always after unix epoch; qed")
+			.as_millis() as u64;
+		let next = last.saturating_add(node_template_runtime::SLOT_DURATION).max(now);
+
+		ManualSealTimestamp { next: AtomicU64::new(next) }
+	}
+}
+
+/// The `Timestamp::now` of the best block of `client`, zero at genesis.
+pub fn best_block_timestamp<C>(client: &C) -> Result<u64, ServiceError>
+where
+	C: StorageProvider<Block, TFullBackend<Block>> + HeaderBackend<Block>,
+{
+	let key = [twox_128(b"Timestamp"), twox_128(b"Now")].concat();
+	let best = BlockId::Hash(client.info().best_hash);
+	Ok(client.storage(&best, &StorageKey(key))?
+		.and_then(|data| data.0.as_slice().try_into().ok())
+		.map_or(0, u64::from_le_bytes))
+}
+
+impl ProvideInherentData for ManualSealTimestamp {
+	fn inherent_identifier(&self) -> &'static InherentIdentifier {
+		&sp_timestamp::INHERENT_IDENTIFIER
+	}
+
+	fn provide_inherent_data(&self, inherent_data: &mut InherentData) -> Result<(), sp_inherents::Error> {
+		let timestamp: sp_timestamp::InherentType =
+			self.next.fetch_add(node_template_runtime::SLOT_DURATION, Ordering::SeqCst);
+		inherent_data.put_data(sp_timestamp::INHERENT_IDENTIFIER, &timestamp)
+	}
+
+	fn error_to_string(&self, error: &[u8]) -> Option<String> {
+		sp_timestamp::InherentDataProvider.error_to_string(error)
+	}
+}
+
 /// Builds a new service for a full client.
 pub fn new_full(config: Configuration) -> Result<impl AbstractService, ServiceError> {
 	let role = config.role.clone();
@@ -199,6 +312,89 @@ pub fn new_full(config: Configuration) -> Result<impl AbstractService, ServiceEr
 	Ok(service)
 }
 
+/// Builds a new service for a full client that seals blocks with `sealing` instead of running
+/// Aura and GRANDPA.
+///
+/// Exposes the `engine_createBlock` and `engine_finalizeBlock` RPCs in every mode. With instant
+/// sealing, blocks are sealed both when transactions are imported and on RPC request.
+pub fn new_full_manual_seal(
+	config: Configuration,
+	sealing: Sealing,
+) -> Result<impl AbstractService, ServiceError> {
+	let (builder, inherent_data_providers) = new_manual_seal_start!(config);
+
+	let (command_sink, commands_stream) = futures::channel::mpsc::channel(1024);
+	let rpc_sink = command_sink.clone();
+
+	let service = builder
+		.with_rpc_extensions(|_builder| -> Result<_, ServiceError> {
+			let mut io = jsonrpc_core::IoHandler::default();
+			io.extend_with(ManualSealApi::to_delegate(ManualSeal::new(rpc_sink)));
+			Ok(io)
+		})?
+		.build()?;
+
+	let proposer = sc_basic_authorship::ProposerFactory::new(
+		service.client(),
+		service.transaction_pool(),
+		service.prometheus_registry().as_ref(),
+	);
+
+	let client = service.client();
+	let pool = service.transaction_pool().pool().clone();
+	let select_chain = service.select_chain()
+		.ok_or(ServiceError::SelectChainRequired)?;
+
+	let commands_stream = match sealing {
+		Sealing::Instant => {
+			// What `run_instant_seal` does, merged with the RPC's commands.
+			let instant = pool.validated_pool()
+				.import_notification_stream()
+				.map(|_| EngineCommand::SealNewBlock {
+					create_empty: false,
+					finalize: false,
+					parent_hash: None,
+					sender: None,
+				});
+			futures::stream::select(instant, commands_stream).boxed()
+		}
+		Sealing::Manual | Sealing::Interval(_) => {
+			if let Sealing::Interval(millis) = sealing {
+				let mut interval_sink = command_sink;
+				service.spawn_task("manual-seal-interval", async move {
+					loop {
+						futures_timer::Delay::new(Duration::from_millis(millis)).await;
+						let command = EngineCommand::SealNewBlock {
+							create_empty: true,
+							finalize: true,
+							parent_hash: None,
+							sender: None,
+						};
+						if interval_sink.send(command).await.is_err() {
+							break;
+						}
+					}
+				});
+			}
+			commands_stream.boxed()
+		}
+	};
+
+	let authorship = sc_consensus_manual_seal::run_manual_seal(
+		Box::new(client.clone()),
+		proposer,
+		client,
+		pool,
+		commands_stream,
+		select_chain,
+		inherent_data_providers,
+	);
+
+	service.spawn_essential_task("manual-seal", authorship);
+
+	Ok(service)
+}
+
 /// Builds a new service for a light client.
 pub fn new_light(config: Configuration) -> Result<impl AbstractService, ServiceError> {
 	let inherent_data_providers = InherentDataProviders::new();
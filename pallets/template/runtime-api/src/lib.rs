@@ -0,0 +1,24 @@
+//! Runtime API for inspecting the DAO pallet from outside the runtime.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	/// Read access to the DAO's state.
+	#[api_version(2)]
+	pub trait DaoApi<AccountId, BlockNumber> where
+		AccountId: Codec,
+		BlockNumber: Codec,
+	{
+		/// Check the DAO's storage invariants, returning a description of the first one that
+		/// is violated.
+		fn try_state() -> Result<(), Vec<u8>>;
+
+		/// Shares `account` held in the DAO `dao_id` at the end of `block`, as recorded by the
+		/// share checkpoints votes are weighed with. These only reach back to the oldest snapshot
+		/// of an unprocessed proposal.
+		fn shares_at(dao_id: u32, account: AccountId, block: BlockNumber) -> u128;
+	}
+}
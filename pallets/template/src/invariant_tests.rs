@@ -0,0 +1,256 @@
+// Property-based tests driving the DAO through random sequences of actions.
+//
+// Every generated action is dispatched as-is, so most of them fail validation; the point is that
+// whatever sequence of successes and failures occurs, the DAO's invariants hold after every step.
+
+use crate::{
+	Conviction, ConvictionBonus, DaoParams, DelegatedVotes, Shares, Vote, VotesByMember, VotingMode, mock::*,
+};
+use frame_support::IterableStorageDoubleMap;
+use frame_support::traits::Get;
+use proptest::{collection::vec, prelude::*};
+
+/// Accounts taking part in the generated sequences. Accounts 1 to 3 are summoning members.
+const ACCOUNTS: AccountId = 6;
+
+/// Free balance every account starts with.
+const ENDOWMENT: u64 = 1_000;
+
+#[derive(Clone, Debug)]
+enum Action {
+	Submit { proposer: AccountId, applicant: AccountId, shares: Shares, loot: Shares, tribute: u64, payment: u64 },
+	SubmitGuildKick { proposer: AccountId, member: AccountId },
+	SubmitParams { proposer: AccountId, voting: u64, grace: u64, deposit: u64, quadratic: bool },
+	Sponsor { delegate: AccountId, proposal: u64 },
+	Vote { delegate: AccountId, index: u64, vote: Vote },
+	QuadraticVote { delegate: AccountId, index: u64, vote: Vote, shares: Shares },
+	ConvictionVote { delegate: AccountId, index: u64, vote: Vote, conviction: Conviction },
+	Process { who: AccountId, index: u64 },
+	Cancel { proposer: AccountId, proposal: u64 },
+	Ragequit { member: AccountId, shares: Shares, loot: Shares },
+	Ragekick { member: AccountId },
+	Withdraw { who: AccountId, amount: u64 },
+	UpdateDelegateKey { member: AccountId, delegate: AccountId },
+	DelegateVotes { member: AccountId, delegate: AccountId },
+	UndelegateVotes { member: AccountId },
+	AdvanceBlocks(u64),
+}
+
+fn account() -> impl Strategy<Value = AccountId> {
+	1..=ACCOUNTS
+}
+
+fn vote() -> impl Strategy<Value = Vote> {
+	prop_oneof![Just(Vote::Yes), Just(Vote::No), Just(Vote::Abstain)]
+}
+
+fn conviction() -> impl Strategy<Value = Conviction> {
+	prop_oneof![
+		Just(Conviction::None),
+		Just(Conviction::Locked2x),
+		Just(Conviction::Locked3x),
+		Just(Conviction::Locked6x),
+	]
+}
+
+fn action() -> impl Strategy<Value = Action> {
+	prop_oneof![
+		4 => (account(), account(), 0..20u128, 0..20u128, 0..300u64, 0..300u64).prop_map(
+			|(proposer, applicant, shares, loot, tribute, payment)| Action::Submit {
+				proposer, applicant, shares, loot, tribute, payment,
+			}
+		),
+		1 => (account(), account()).prop_map(|(proposer, member)| Action::SubmitGuildKick { proposer, member }),
+		1 => (account(), 0..4u64, 0..3u64, 1..30u64, any::<bool>()).prop_map(
+			|(proposer, voting, grace, deposit, quadratic)| Action::SubmitParams {
+				proposer, voting, grace, deposit, quadratic,
+			}
+		),
+		4 => (account(), any::<u64>()).prop_map(|(delegate, proposal)| Action::Sponsor { delegate, proposal }),
+		6 => (account(), any::<u64>(), vote()).prop_map(|(delegate, index, vote)| Action::Vote {
+			delegate, index, vote,
+		}),
+		3 => (account(), any::<u64>(), vote(), 0..12u128).prop_map(|(delegate, index, vote, shares)| {
+			Action::QuadraticVote { delegate, index, vote, shares }
+		}),
+		2 => (account(), any::<u64>(), vote(), conviction()).prop_map(|(delegate, index, vote, conviction)| {
+			Action::ConvictionVote { delegate, index, vote, conviction }
+		}),
+		4 => (account(), any::<u64>()).prop_map(|(who, index)| Action::Process { who, index }),
+		1 => (account(), any::<u64>()).prop_map(|(proposer, proposal)| Action::Cancel { proposer, proposal }),
+		2 => (account(), 0..10u128, 0..10u128).prop_map(|(member, shares, loot)| Action::Ragequit {
+			member, shares, loot,
+		}),
+		1 => account().prop_map(|member| Action::Ragekick { member }),
+		2 => (account(), 0..300u64).prop_map(|(who, amount)| Action::Withdraw { who, amount }),
+		1 => (account(), account()).prop_map(|(member, delegate)| Action::UpdateDelegateKey { member, delegate }),
+		2 => (account(), account()).prop_map(|(member, delegate)| Action::DelegateVotes { member, delegate }),
+		1 => account().prop_map(|member| Action::UndelegateVotes { member }),
+		4 => (1..4u64).prop_map(Action::AdvanceBlocks),
+	]
+}
+
+fn new_dao() -> sp_io::TestExternalities {
+	ExtBuilder::default()
+		.members(vec![(1, 10), (2, 5), (3, 1)])
+		.balances((1..=ACCOUNTS).map(|who| (who, ENDOWMENT)).collect())
+		.build()
+}
+
+/// Map an arbitrary number onto an existing proposal id or queue index, if any.
+fn pick(raw: u64, count: u64) -> u64 {
+	raw % count.max(1)
+}
+
+/// Dispatch `action`, ignoring whether it succeeds.
+fn apply(action: Action) {
+	let _ = match action {
+		Action::Submit { proposer, applicant, shares, loot, tribute, payment } =>
+			TemplateModule::submit_proposal(Origin::signed(proposer), 0, applicant, shares, loot, tribute, payment, vec![]),
+		Action::SubmitGuildKick { proposer, member } =>
+			TemplateModule::submit_guild_kick_proposal(Origin::signed(proposer), 0, member, vec![]),
+		Action::SubmitParams { proposer, voting, grace, deposit, quadratic } => {
+			let params = DaoParams {
+				voting_period_length: voting,
+				grace_period_length: grace,
+				proposal_deposit: deposit,
+				voting_mode: if quadratic { VotingMode::Quadratic } else { VotingMode::ShareWeighted },
+				..TemplateModule::dao(0).unwrap()
+			};
+			TemplateModule::submit_params_proposal(Origin::signed(proposer), 0, params, vec![])
+		}
+		Action::Sponsor { delegate, proposal } =>
+			TemplateModule::sponsor_proposal(Origin::signed(delegate), 0, pick(proposal, TemplateModule::proposal_count(0))),
+		Action::Vote { delegate, index, vote } => TemplateModule::submit_vote(
+			Origin::signed(delegate),
+			0,
+			pick(index, TemplateModule::proposal_queue_length(0)),
+			vote,
+		).map(|_| ()).map_err(|e| e.error),
+		Action::QuadraticVote { delegate, index, vote, shares } => TemplateModule::submit_quadratic_vote(
+			Origin::signed(delegate),
+			0,
+			pick(index, TemplateModule::proposal_queue_length(0)),
+			vote,
+			shares,
+		).map(|_| ()).map_err(|e| e.error),
+		Action::ConvictionVote { delegate, index, vote, conviction } => TemplateModule::submit_conviction_vote(
+			Origin::signed(delegate),
+			0,
+			pick(index, TemplateModule::proposal_queue_length(0)),
+			vote,
+			conviction,
+		).map(|_| ()).map_err(|e| e.error),
+		Action::Process { who, index } => TemplateModule::process_proposal(
+			Origin::signed(who),
+			0,
+			pick(index, TemplateModule::proposal_queue_length(0)),
+		).map(|_| ()).map_err(|e| e.error),
+		Action::Cancel { proposer, proposal } =>
+			TemplateModule::cancel_proposal(Origin::signed(proposer), 0, pick(proposal, TemplateModule::proposal_count(0))),
+		Action::Ragequit { member, shares, loot } =>
+			TemplateModule::ragequit(Origin::signed(member), 0, shares, loot),
+		Action::Ragekick { member } =>
+			TemplateModule::ragekick(Origin::signed(member), 0, member),
+		Action::Withdraw { who, amount } =>
+			TemplateModule::withdraw_balance(Origin::signed(who), 0, amount),
+		Action::UpdateDelegateKey { member, delegate } =>
+			TemplateModule::update_delegate_key(Origin::signed(member), 0, delegate),
+		Action::DelegateVotes { member, delegate } =>
+			TemplateModule::delegate_votes(Origin::signed(member), 0, delegate),
+		Action::UndelegateVotes { member } =>
+			TemplateModule::undelegate_votes(Origin::signed(member), 0),
+		Action::AdvanceBlocks(blocks) => {
+			// Runs `do_try_state` at the end of every block.
+			run_to_block(System::block_number() + blocks);
+			Ok(())
+		}
+	};
+}
+
+/// Invariants that hold on top of `do_try_state` in this closed setting without fees.
+fn check_invariants() {
+	assert_eq!(TemplateModule::do_try_state(), Ok(()));
+
+	// No funds are created or destroyed.
+	assert_eq!(Balances::total_issuance(), ACCOUNTS as u64 * ENDOWMENT + ExistentialDeposit::get());
+
+	// The guild bank holds exactly the internal balances on top of its existential deposit, so
+	// rounding never leaks funds into or out of the bank.
+	let internal: u64 = TemplateModule::guild_balance(0)
+		+ TemplateModule::escrow_balance(0)
+		+ (1..=ACCOUNTS).map(|who| TemplateModule::user_balance(0, who)).sum::<u64>();
+	assert_eq!(Balances::free_balance(TemplateModule::account_id(0)), internal + ExistentialDeposit::get());
+
+	// Deposits are only reserved for sponsored, unprocessed proposals, at the amount that
+	// applied when each was submitted.
+	let pending_deposits: u64 = (0..TemplateModule::proposal_queue_length(0))
+		.filter_map(|index| TemplateModule::proposal_queue(0, index))
+		.filter_map(|id| TemplateModule::proposal_params(0, id))
+		.map(|params| params.proposal_deposit)
+		.sum();
+	let reserved: u64 = (1..=ACCOUNTS).map(|who| Balances::reserved_balance(who)).sum();
+	assert_eq!(reserved, pending_deposits);
+
+	// The tallies of every proposal are the sum of its ballots.
+	for proposal_id in 0..TemplateModule::proposal_count(0) {
+		let proposal = TemplateModule::proposals(0, proposal_id).unwrap();
+		let mut tallies = (0, 0, 0);
+		for (_, ballot) in <VotesByMember<Test>>::iter_prefix((0, proposal_id)) {
+			match ballot.vote {
+				Vote::Yes => tallies.0 += ballot.shares,
+				Vote::No => tallies.1 += ballot.shares,
+				Vote::Abstain => tallies.2 += ballot.shares,
+			}
+		}
+		assert_eq!(tallies, (proposal.yes_votes, proposal.no_votes, proposal.abstain_votes));
+
+		// Ballots hold at least the shares they counted for delegators who did not vote.
+		for (who, ballot) in <VotesByMember<Test>>::iter_prefix((0, proposal_id)) {
+			let delegated: Shares = <DelegatedVotes<Test>>::iter_prefix((0, proposal_id))
+				.filter(|(_, (delegate, _))| *delegate == who)
+				.map(|(_, (_, shares))| shares)
+				.sum();
+			assert!(delegated <= ballot.shares);
+		}
+		for (who, _) in <DelegatedVotes<Test>>::iter_prefix((0, proposal_id)) {
+			assert!(TemplateModule::vote_of((0, proposal_id), who).is_none());
+		}
+
+		// The conviction tally is the sum of the conviction of yes and no ballots.
+		let mut conviction = (0, 0);
+		for (who, bonus) in <ConvictionBonus<Test>>::iter_prefix((0, proposal_id)) {
+			match TemplateModule::vote_of((0, proposal_id), who).map(|ballot| ballot.vote) {
+				Some(Vote::Yes) => conviction.0 += bonus,
+				Some(Vote::No) => conviction.1 += bonus,
+				_ => {}
+			}
+		}
+		assert_eq!(conviction, TemplateModule::conviction_tally(0, proposal_id));
+	}
+
+	// Rounding down means all members ragequitting at once can never take more than the guild
+	// bank holds.
+	let guild = TemplateModule::guild_balance(0);
+	let total = TemplateModule::total_shares_and_loot(0);
+	let fair_shares: u64 = (1..=ACCOUNTS)
+		.filter_map(|who| TemplateModule::members(0, who))
+		.map(|m| TemplateModule::fair_share(guild, m.shares + m.loot, total))
+		.sum();
+	assert!(fair_shares <= guild);
+}
+
+proptest! {
+	#![proptest_config(ProptestConfig::with_cases(128))]
+
+	#[test]
+	fn invariants_hold_for_random_action_sequences(actions in vec(action(), 1..150)) {
+		new_dao().execute_with(|| {
+			check_invariants();
+			for action in actions {
+				apply(action);
+				check_invariants();
+			}
+		});
+	}
+}
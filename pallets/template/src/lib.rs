@@ -1,109 +1,3715 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-/// A FRAME pallet template with necessary imports
+//! A Moloch-style DAO pallet.
+//!
+//! Any number of independent DAOs live side by side, each identified by a [`DaoId`] and with its
+//! own [`DaoParams`], members, proposal queue and guild bank. The first DAO is summoned at genesis
+//! with the parameters of the pallet's `Trait`, anyone can `summon` more. A DAO changes its
+//! parameters through parameter-change proposals, each proposal keeps the parameters that applied
+//! when it was submitted.
+//!
+//! Each kind of proposal needs its own [`Threshold`] of yes votes and quorum to pass, so that for
+//! example parameter changes can require a supermajority while funding passes on a simple one.
+//!
+//! Members hold voting `shares` and non-voting `loot` that both entitle them to a fair share of
+//! the guild bank. Anyone can submit a proposal offering tribute in exchange for shares, loot or
+//! a payment from the guild bank. A member sponsors it into the processing queue, members vote
+//! on it during the voting period and, once the grace period has passed, anyone can process it.
+//! Members that disagree with a passed proposal can `ragequit` during the grace period and leave
+//! with their fair share of the guild bank.
+//!
+//! Funds are tracked with internal balances: tribute waits in escrow until its proposal is
+//! processed, the guild balance belongs to the members, and user balances can be withdrawn at
+//! any time. All of them are held by the DAO's guild bank, a sub-account of `Trait::ModuleId`.
+//!
+//! Action proposals carry a call that is dispatched with the DAO's own origin, `RawOrigin::Dao`,
+//! if they pass. Other pallets use [`EnsureDao`] or [`EnsureDaoId`] to let DAOs govern them.
+//!
+//! A DAO decides proposals in one of two [`VotingMode`]s. By default votes are weighted by
+//! shares, as in Moloch. In quadratic mode members commit shares to each vote, which weighs the
+//! integer square root of the committed shares, so that a few large members cannot dominate.
+//!
+//! In share-weighted mode members can vote with [`Conviction`], multiplying the weight of their
+//! shares by locking them against ragequit for longer after voting ends. Conviction counts
+//! towards approval, quorum only counts the votes themselves.
+//!
+//! Members may delegate their voting weight to another member they trust, who may delegate it
+//! further. A member's vote carries the weight of everyone whose delegations lead to them, up to
+//! `Trait::MaxDelegationDepth` delegations away, unless they voted on the proposal themselves.
+//! Delegated weight only counts in share-weighted mode.
+//!
+//! Votes weigh the shares members held in the block the proposal was sponsored in, looked up in
+//! checkpoints kept for every change of a member's shares, so that shares received mid-vote
+//! cannot swing it. Quorum is measured against the total shares at that block.
+//!
+//! Share-weighted DAOs may hold secret ballots by setting a reveal period. Members then commit
+//! to the hash of their vote and a salt while voting is open and reveal it in the reveal period
+//! that follows. Unrevealed votes are not counted and their reveal deposit goes to the guild bank.
+//!
+//! A DAO may hold the payments and actions of passed proposals in a timelock, giving members who
+//! disagree time to ragequit before they take effect. Guardians the DAO appoints can cancel them
+//! while they wait. Guardians can also veto a proposal during its grace period, which members
+//! then vote on ratifying, as a defence against an attacker that briefly holds a majority.
+//!
+//! Funding proposals may pay out a grant that vests on a [`VestingSchedule`] instead of a lump
+//! sum. Grants leave the guild balance when they start and vest block by block after a cliff.
+//! Recipients claim what has vested, and the unvested rest returns to the guild bank if they
+//! are kicked from the guild or the DAO revokes the grant.
+//!
+//! Grants may also be paid by milestone. The first tranche is paid when the proposal passes and
+//! the rest stays reserved in the guild bank, each tranche paid once members holding more than
+//! half of the shares approve its milestone. Cancelling the grant, or kicking its recipient,
+//! releases what is left.
+//!
+//! In an emergency any guardian, or members holding more than half of the shares, can pause the
+//! DAO, leaving only ragequit, withdrawals and votes open.
+//!
+//! Votes do not pay transaction fees. Runtimes must include the [`ValidateDaoCall`] signed
+//! extension, which only lets votes from members into the transaction pool and rate limits them
+//! by numbering each member's votes in a period, and keeps calls a paused DAO would reject out of
+//! the pool.
+//!
+//! The module implements `OnUnbalanced`, so runtimes can pay transaction fees, dust and other
+//! imbalances into the guild bank of the genesis DAO.
 
-/// Feel free to remove or edit this file as needed.
-/// If you change the name of this file, make sure to update its references in runtime/src/lib.rs
-/// If you remove this file, you can remove those references
-
-/// For more guidance on Substrate FRAME, see the example pallet
-/// https://github.com/paritytech/substrate/blob/master/frame/example/src/lib.rs
-
-use frame_support::{decl_module, decl_storage, decl_event, decl_error, dispatch};
+use sp_std::{prelude::*, marker::PhantomData};
+use codec::{Encode, Decode};
+use frame_support::{
+	decl_module, decl_storage, decl_event, decl_error, ensure, IterableStorageDoubleMap,
+	dispatch::{DispatchError, DispatchResult, DispatchResultWithPostInfo, Parameter, PostDispatchInfo},
+	traits::{
+		Currency, ReservableCurrency, ExistenceRequirement, BalanceStatus, Get, IsSubType, Imbalance,
+		OnUnbalanced, EnsureOrigin,
+	},
+	weights::{Weight, Pays, GetDispatchInfo},
+};
+use sp_runtime::{
+	ModuleId, Perbill, RuntimeDebug,
+	helpers_128bit::multiply_by_rational,
+	traits::{
+		AccountIdConversion, One, Saturating, Zero, IntegerSquareRoot, SaturatedConversion, SignedExtension, DispatchInfoOf,
+		Dispatchable, Hash as HashT, CheckedAdd,
+	},
+	transaction_validity::{
+		ValidTransaction, TransactionValidity, TransactionValidityError, InvalidTransaction,
+	},
+};
 use frame_system::{self as system, ensure_signed};
 
+mod migration;
+
 #[cfg(test)]
 mod mock;
 
 #[cfg(test)]
 mod tests;
 
+#[cfg(test)]
+mod invariant_tests;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+
+/// Identifier of a DAO.
+pub type DaoId = u32;
+
+/// The DAO summoned at genesis, which receives imbalances paid to this module.
+pub const GENESIS_DAO: DaoId = 0;
+
+/// Identifier of a submitted proposal.
+pub type ProposalId = u64;
+
+/// Position of a sponsored proposal in the processing queue.
+pub type ProposalIndex = u64;
+
+/// Amount of shares or loot held by a member.
+pub type Shares = u128;
+
 /// The pallet's configuration trait.
 pub trait Trait: system::Trait {
-	// Add other types and constants required to configure this pallet.
-
 	/// The overarching event type.
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// The outer origin type, which includes the DAO's origin.
+	type Origin: From<RawOrigin>;
+
+	/// The outer call type, dispatched by passed action proposals.
+	type Proposal: Parameter
+		+ Dispatchable<Origin = <Self as Trait>::Origin, PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo;
+
+	/// Maximum weight of the call of an action proposal.
+	type MaxActionWeight: Get<Weight>;
+
+	/// The currency used for tribute, payments, deposits and the guild bank.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The pallet's id, used to derive the guild bank accounts holding each DAO's funds.
+	type ModuleId: Get<ModuleId>;
+
+	/// Number of blocks in a period of the genesis DAO.
+	type PeriodDuration: Get<Self::BlockNumber>;
+
+	/// Number of periods a sponsored proposal of the genesis DAO is open for voting.
+	type VotingPeriodLength: Get<u64>;
+
+	/// Grace period of the genesis DAO, see [`DaoParams::grace_period_length`].
+	type GracePeriodLength: Get<u64>;
+
+	/// Proposal deposit of the genesis DAO, see [`DaoParams::proposal_deposit`].
+	type ProposalDeposit: Get<BalanceOf<Self>>;
+
+	/// Processing reward of the genesis DAO, see [`DaoParams::processing_reward`].
+	type ProcessingReward: Get<BalanceOf<Self>>;
+
+	/// Dilution bound of the genesis DAO, see [`DaoParams::dilution_bound`].
+	type DilutionBound: Get<u128>;
+
+	/// Thresholds of the genesis DAO, see [`DaoParams::thresholds`].
+	type Thresholds: Get<Thresholds>;
+
+	/// Number of votes per period of any DAO whose fee is refunded to the member. Later votes in
+	/// the period pay their fees.
+	///
+	/// The refund alone keeps free votes bounded, but the runtime should include
+	/// [`ValidateDaoCall`] in its `SignedExtra` so that votes that would fail or pay are kept out
+	/// of the transaction pool. It adds the vote's number to the encoding of every extrinsic.
+	type MaxVotesPerPeriod: Get<u32>;
+
+	/// Maximum number of delegations a member's voting weight may follow to the member who votes
+	/// with it.
+	type MaxDelegationDepth: Get<u32>;
+
+	/// Maximum number of members that may delegate their voting weight to the same member.
+	type MaxDelegators: Get<u32>;
+
+	/// Maximum number of unprocessed proposals a member may commit shares to in quadratic mode.
+	type MaxCommitments: Get<u32>;
+
+	/// Origin of a DAO managing itself, such as setting its guardians, which yields the DAO's id.
+	/// Normally [`EnsureDao`].
+	type DaoOrigin: EnsureOrigin<<Self as system::Trait>::Origin, Success = DaoId>;
+
+	/// Maximum number of guardians of a DAO.
+	type MaxGuardians: Get<u32>;
+
+	/// Maximum number of vesting grants an account may hold from the same DAO at once.
+	type MaxGrants: Get<u32>;
+
+	/// Maximum number of milestones of a milestone grant.
+	type MaxMilestones: Get<u32>;
+
+	/// Maximum number of milestone grants a member may support approving the next milestone of
+	/// at once.
+	type MaxMilestoneApprovals: Get<u32>;
+
+	/// Maximum number of share checkpoints kept per member, at least two. Checkpoints no vote
+	/// can be weighed with anymore are dropped first.
+	type MaxCheckpoints: Get<u32>;
+
+	/// Distinct members and shares of the DAO summoned by the upgrade from the node template's
+	/// storage, the first of which endows its guild bank. Only read by that migration.
+	type TemplateSummoners: Get<Vec<(Self::AccountId, Shares)>>;
+}
+
+/// Origin for the DAO.
+#[derive(PartialEq, Eq, Clone, RuntimeDebug, Encode, Decode)]
+pub enum RawOrigin {
+	/// A DAO, dispatching the call of one of its passed action proposals.
+	Dao(DaoId),
+}
+
+/// Origin for the DAO pallet.
+pub type Origin = RawOrigin;
+
+/// Parameters of a DAO, set when it is summoned and changed by parameter-change proposals.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct DaoParams<BlockNumber, Balance> {
+	/// Number of blocks in a period.
+	pub period_duration: BlockNumber,
+	/// Number of periods a sponsored proposal is open for voting.
+	pub voting_period_length: u64,
+	/// Number of periods after voting ends during which members may ragequit before the
+	/// proposal can be processed.
+	pub grace_period_length: u64,
+	/// Deposit reserved from the sponsor of a proposal until it is processed.
+	pub proposal_deposit: Balance,
+	/// Part of the proposal deposit paid to whoever processes the proposal.
+	pub processing_reward: Balance,
+	/// Maximum factor by which total shares and loot may shrink between a member's yes vote and
+	/// processing before the proposal automatically fails.
+	pub dilution_bound: u128,
+	/// Votes each kind of proposal needs to pass.
+	pub thresholds: Thresholds,
+	/// How votes are weighed.
+	pub voting_mode: VotingMode,
+	/// Number of periods after voting ends during which members reveal the votes they committed
+	/// to, or zero for open ballots. Secret ballots need share-weighted voting.
+	pub reveal_period_length: u64,
+	/// Deposit reserved for each committed vote until it is revealed. Deposits of votes that are
+	/// never revealed go to the guild bank.
+	pub reveal_deposit: Balance,
+	/// Number of periods the payment or action of a passed proposal waits before it can be
+	/// executed, or zero to execute it when the proposal is processed.
+	pub timelock_periods: u64,
+}
+
+/// How the votes of members are weighed.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum VotingMode {
+	/// Every vote weighs the member's shares and the shares delegated to them, as in Moloch.
+	ShareWeighted,
+	/// Every vote weighs the integer square root of the shares the member commits to it. The
+	/// shares a member commits to unprocessed proposals add up to at most their shares and
+	/// cannot be ragequit. Quorum counts committed shares.
+	Quadratic,
+}
+
+impl Default for VotingMode {
+	fn default() -> Self {
+		VotingMode::ShareWeighted
+	}
+}
+
+/// Kinds of proposals, which may need different votes to pass.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ProposalKind {
+	/// A proposal asking for a payment or only offering tribute.
+	Funding,
+	/// A proposal asking for shares or loot.
+	Membership,
+	/// A parameter-change proposal.
+	ParameterChange,
+	/// An action proposal, such as a runtime upgrade or a change of the validator set.
+	Action,
+	/// A guild kick proposal.
+	GuildKick,
+}
+
+/// Votes a proposal needs to pass.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct Threshold {
+	/// Part of the votes cast that must be yes votes. The proposal passes if yes votes exceed it.
+	pub approval: Perbill,
+	/// Part of the DAO's total shares that must be cast as votes, rounded down.
+	pub quorum: Perbill,
+}
+
+/// A simple majority without quorum, Moloch's rule of more yes than no votes.
+impl Default for Threshold {
+	fn default() -> Self {
+		Threshold { approval: Perbill::from_percent(50), quorum: Perbill::zero() }
+	}
+}
+
+/// The [`Threshold`] of each [`ProposalKind`].
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct Thresholds {
+	/// Threshold of funding proposals.
+	pub funding: Threshold,
+	/// Threshold of membership proposals.
+	pub membership: Threshold,
+	/// Threshold of parameter-change proposals.
+	pub parameter_change: Threshold,
+	/// Threshold of action proposals.
+	pub action: Threshold,
+	/// Threshold of guild kick proposals.
+	pub guild_kick: Threshold,
+}
+
+impl Thresholds {
+	/// The threshold of proposals of `kind`.
+	pub fn of(&self, kind: ProposalKind) -> &Threshold {
+		match kind {
+			ProposalKind::Funding => &self.funding,
+			ProposalKind::Membership => &self.membership,
+			ProposalKind::ParameterChange => &self.parameter_change,
+			ProposalKind::Action => &self.action,
+			ProposalKind::GuildKick => &self.guild_kick,
+		}
+	}
+}
+
+/// A member's vote on a proposal.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Vote {
+	Yes,
+	No,
+	/// Counts towards quorum, but neither for nor against the proposal.
+	Abstain,
+}
+
+/// How strongly a member stands by a vote, multiplying the weight of their own shares in return
+/// for locking them after voting ends.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Conviction {
+	/// Shares count once and are not locked.
+	None,
+	/// Shares count twice and are locked for one voting period after voting ends.
+	Locked2x,
+	/// Shares count three times and are locked for two voting periods.
+	Locked3x,
+	/// Shares count four times and are locked for four voting periods.
+	Locked4x,
+	/// Shares count five times and are locked for eight voting periods.
+	Locked5x,
+	/// Shares count six times and are locked for sixteen voting periods.
+	Locked6x,
+}
+
+impl Default for Conviction {
+	fn default() -> Self {
+		Conviction::None
+	}
+}
+
+impl Conviction {
+	/// Number of times the member's shares count.
+	pub fn multiplier(self) -> Shares {
+		match self {
+			Conviction::None => 1,
+			Conviction::Locked2x => 2,
+			Conviction::Locked3x => 3,
+			Conviction::Locked4x => 4,
+			Conviction::Locked5x => 5,
+			Conviction::Locked6x => 6,
+		}
+	}
+
+	/// Number of voting periods the member's shares are locked for after voting ends.
+	pub fn lock_periods(self) -> u64 {
+		match self {
+			Conviction::None => 0,
+			Conviction::Locked2x => 1,
+			Conviction::Locked3x => 2,
+			Conviction::Locked4x => 4,
+			Conviction::Locked5x => 8,
+			Conviction::Locked6x => 16,
+		}
+	}
+}
+
+/// A guardians' veto of a proposal and the members' vote on ratifying it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct Veto {
+	/// Period of the DAO at which the ratification vote ends.
+	pub ratification_ends: u64,
+	/// Shares voting to ratify the veto.
+	pub ratify: Shares,
+	/// Shares voting to overturn the veto.
+	pub overturn: Shares,
+}
+
+/// How the payment of a funding proposal vests once it passes.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct VestingSchedule<BlockNumber> {
+	/// Number of blocks after the grant starts before anything vests.
+	pub cliff: BlockNumber,
+	/// Number of blocks over which the whole payment vests, an equal part every block.
+	pub duration: BlockNumber,
+}
+
+/// A payment vesting to its recipient.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct Grant<Balance, BlockNumber> {
+	/// The whole payment.
+	pub total: Balance,
+	/// Part of the payment the recipient has claimed.
+	pub claimed: Balance,
+	/// Block the grant started in.
+	pub start: BlockNumber,
+	/// The schedule the payment vests on.
+	pub schedule: VestingSchedule<BlockNumber>,
+}
+
+/// A milestone of a grant and the tranche paid once members approve it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct Milestone<Balance, Hash> {
+	/// The tranche paid for the milestone.
+	pub amount: Balance,
+	/// Hash of the deliverable members approve the milestone on.
+	pub deliverable: Hash,
+}
+
+/// A grant paid in tranches as its milestones are approved.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct MilestoneGrant<AccountId, Balance, Hash> {
+	/// The account the tranches are paid to.
+	pub recipient: AccountId,
+	/// All milestones of the grant, in order.
+	pub milestones: Vec<Milestone<Balance, Hash>>,
+	/// Index of the milestone members vote on approving next.
+	pub next: u32,
+	/// Shares supporting approval of the next milestone.
+	pub support: Shares,
+}
+
+/// A member's commitment to a secret vote on a proposal.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct SealedVote<AccountId, Balance, Hash> {
+	/// Hash of the vote and a salt of the member's choosing.
+	pub hash: Hash,
+	/// Account the reveal deposit is reserved from.
+	pub depositor: AccountId,
+	/// Reveal deposit.
+	pub deposit: Balance,
+	/// Period of the DAO at which the reveal period ends.
+	pub reveal_ends: u64,
+}
+
+/// A member's vote on a proposal and the shares it was counted with.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct Ballot {
+	/// The member's current vote.
+	pub vote: Vote,
+	/// Shares added to the tally of `vote`, the member's own and those of delegators who did not
+	/// vote themselves. They are taken off again if the vote changes.
+	pub shares: Shares,
+}
+
+/// A member of the DAO.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct Member<AccountId> {
+	/// The key the member uses to sponsor proposals and vote.
+	pub delegate_key: AccountId,
+	/// Voting shares held by the member.
+	pub shares: Shares,
+	/// Non-voting shares held by the member.
+	pub loot: Shares,
+	/// Highest queue index of a proposal the member voted yes on, which must be processed before
+	/// the member can ragequit.
+	pub highest_index_yes_vote: Option<ProposalIndex>,
+	/// Whether the member has been kicked from the guild.
+	pub jailed: bool,
+}
+
+/// A proposal to the DAO.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct Proposal<AccountId, Balance> {
+	/// The account receiving shares, loot and payment, or the member to kick.
+	pub applicant: AccountId,
+	/// The account that submitted the proposal and offered the tribute.
+	pub proposer: AccountId,
+	/// The delegate key that sponsored the proposal and paid its deposit.
+	pub sponsor: Option<AccountId>,
+	/// Shares requested for the applicant.
+	pub shares_requested: Shares,
+	/// Loot requested for the applicant.
+	pub loot_requested: Shares,
+	/// Tribute offered by the proposer, held in escrow until processing.
+	pub tribute_offered: Balance,
+	/// Payment requested from the guild bank for the applicant.
+	pub payment_requested: Balance,
+	/// Period in which voting starts.
+	pub starting_period: u64,
+	/// Shares voted in favour.
+	pub yes_votes: Shares,
+	/// Shares voted against.
+	pub no_votes: Shares,
+	/// Shares abstaining.
+	pub abstain_votes: Shares,
+	/// Highest total of shares and loot at the time of any yes vote.
+	pub max_total_shares_and_loot_at_yes_vote: Shares,
+	/// Whether the proposal has been sponsored into the queue.
+	pub sponsored: bool,
+	/// Whether the proposal has been processed.
+	pub processed: bool,
+	/// Whether the proposal passed when it was processed.
+	pub did_pass: bool,
+	/// Whether the proposer cancelled the proposal before sponsorship.
+	pub cancelled: bool,
+	/// Whether the proposal kicks `applicant` from the guild.
+	pub guild_kick: bool,
+	/// Free-form description of the proposal.
+	pub details: Vec<u8>,
+}
+
+/// Storage layouts of this pallet, in order of introduction.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+enum Releases {
+	/// The node template's `Something` value.
+	V0,
+	/// The DAO layout.
+	V1,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V0
+	}
 }
 
 // This pallet's storage items.
 decl_storage! {
-	// It is important to update your storage name so that your pallet's
-	// storage items are isolated from other pallets.
-	// ---------------------------------vvvvvvvvvvvvvv
 	trait Store for Module<T: Trait> as TemplateModule {
-		// Just a dummy storage item.
-		// Here we are declaring a StorageValue, `Something` as a Option<u32>
-		// `get(fn something)` is the default getter which returns either the stored `u32` or `None` if nothing stored
-		Something get(fn something): Option<u32>;
+		/// Storage layout version, used to pick the migrations to run on runtime upgrade.
+		StorageVersion build(|_: &GenesisConfig<T>| Releases::V1): Releases;
+
+		/// Number of DAOs ever summoned, and the id of the next one.
+		DaoCount get(fn dao_count): DaoId;
+
+		/// Parameters of each DAO.
+		Daos get(fn dao): map hasher(twox_64_concat) DaoId => Option<DaoParams<T::BlockNumber, BalanceOf<T>>>;
+
+		/// Block at which each DAO was summoned, the start of its period zero.
+		SummoningTime get(fn summoning_time): map hasher(twox_64_concat) DaoId => T::BlockNumber;
+
+		/// Total voting shares across all members.
+		TotalShares get(fn total_shares): map hasher(twox_64_concat) DaoId => Shares;
+
+		/// Total loot across all members.
+		TotalLoot get(fn total_loot): map hasher(twox_64_concat) DaoId => Shares;
+
+		/// Members by their account.
+		Members get(fn members):
+			double_map hasher(twox_64_concat) DaoId, hasher(blake2_128_concat) T::AccountId
+			=> Option<Member<T::AccountId>>;
+
+		/// Member accounts by their delegate key.
+		MemberByDelegateKey get(fn member_by_delegate_key):
+			double_map hasher(twox_64_concat) DaoId, hasher(blake2_128_concat) T::AccountId
+			=> Option<T::AccountId>;
+
+		/// Number of proposals ever submitted, and the id of the next one.
+		ProposalCount get(fn proposal_count): map hasher(twox_64_concat) DaoId => ProposalId;
+
+		/// Submitted proposals by id.
+		Proposals get(fn proposals):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId
+			=> Option<Proposal<T::AccountId, BalanceOf<T>>>;
+
+		/// Ids of sponsored proposals, in processing order.
+		ProposalQueue get(fn proposal_queue):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalIndex => Option<ProposalId>;
+
+		/// Number of proposals ever sponsored, and the index of the next one.
+		ProposalQueueLength get(fn proposal_queue_length): map hasher(twox_64_concat) DaoId => ProposalIndex;
+
+		/// Number of proposals at the start of the queue that are processed, and the index of the
+		/// next one to process.
+		ProcessedQueueLength get(fn processed_queue_length): map hasher(twox_64_concat) DaoId => ProposalIndex;
+
+		/// Ballots cast on each proposal by member account.
+		VotesByMember get(fn vote_of):
+			double_map hasher(twox_64_concat) (DaoId, ProposalId), hasher(blake2_128_concat) T::AccountId
+			=> Option<Ballot>;
+
+		/// The member each member delegates their voting weight to.
+		Delegations get(fn delegation):
+			double_map hasher(twox_64_concat) DaoId, hasher(blake2_128_concat) T::AccountId
+			=> Option<T::AccountId>;
+
+		/// Members delegating their voting weight to each member.
+		Delegators get(fn delegators):
+			double_map hasher(twox_64_concat) DaoId, hasher(blake2_128_concat) T::AccountId
+			=> Vec<T::AccountId>;
+
+		/// For members that did not vote on a proposal, the member whose ballot counted their
+		/// shares and how many.
+		DelegatedVotes get(fn delegated_vote_of):
+			double_map hasher(twox_64_concat) (DaoId, ProposalId), hasher(blake2_128_concat) T::AccountId
+			=> Option<(T::AccountId, Shares)>;
+
+		/// Weight that conviction adds to each ballot on top of its shares.
+		ConvictionBonus get(fn conviction_bonus):
+			double_map hasher(twox_64_concat) (DaoId, ProposalId), hasher(blake2_128_concat) T::AccountId
+			=> Shares;
+
+		/// Weight that conviction adds to the yes and no votes of each proposal.
+		ConvictionTally get(fn conviction_tally):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId => (Shares, Shares);
+
+		/// Shares of each member locked by conviction and the period of the DAO at which the lock
+		/// ends. Later locks extend earlier ones rather than adding up.
+		ConvictionLocks get(fn conviction_lock):
+			double_map hasher(twox_64_concat) DaoId, hasher(blake2_128_concat) T::AccountId
+			=> Option<(u64, Shares)>;
+
+		/// Secret votes each member committed to, by proposal, until they are revealed or their
+		/// deposit goes to the guild bank.
+		SealedVotes get(fn sealed_vote):
+			double_map hasher(twox_64_concat) (DaoId, ProposalId), hasher(blake2_128_concat) T::AccountId
+			=> Option<SealedVote<T::AccountId, BalanceOf<T>, T::Hash>>;
+
+		/// Shares of each member from the block of each change on, oldest first, back to the
+		/// oldest snapshot of an unprocessed proposal and at most `MaxCheckpoints` of them.
+		ShareCheckpoints get(fn share_checkpoints):
+			double_map hasher(twox_64_concat) DaoId, hasher(blake2_128_concat) T::AccountId
+			=> Vec<(T::BlockNumber, Shares)>;
+
+		/// Block before the one each unprocessed proposal was sponsored in and the DAO's total
+		/// shares at sponsorship, which its votes are weighed against.
+		ProposalSnapshots get(fn proposal_snapshot):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId
+			=> Option<(T::BlockNumber, Shares)>;
+
+		/// Period from which the payment or action of each passed proposal in the timelock can be
+		/// executed.
+		Timelocks get(fn timelock):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId => Option<u64>;
+
+		/// Accounts that may cancel payments and actions in the timelock of each DAO, sorted.
+		Guardians get(fn guardians): map hasher(twox_64_concat) DaoId => Vec<T::AccountId>;
+
+		/// Guardians that approved vetoing each proposal in its grace period.
+		VetoApprovals get(fn veto_approvals):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId => Vec<T::AccountId>;
+
+		/// Vetoes of unprocessed proposals.
+		Vetoes get(fn veto): double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId => Option<Veto>;
+
+		/// Votes of members on ratifying the veto of each proposal, true to ratify.
+		RatificationVotes get(fn ratification_vote_of):
+			double_map hasher(twox_64_concat) (DaoId, ProposalId), hasher(blake2_128_concat) T::AccountId
+			=> Option<bool>;
+
+		/// DAOs that are paused, which blocks new proposals, sponsorship, processing and execution.
+		Paused get(fn paused): map hasher(twox_64_concat) DaoId => bool;
+
+		/// Guardians that approved unpausing each paused DAO.
+		UnpauseApprovals get(fn unpause_approvals): map hasher(twox_64_concat) DaoId => Vec<T::AccountId>;
+
+		/// Shares of each member supporting the fast-track vote to pause the DAO, or to unpause it
+		/// while it is paused, that count on the vote.
+		PauseSupport get(fn pause_support):
+			double_map hasher(twox_64_concat) DaoId, hasher(blake2_128_concat) T::AccountId => Shares;
+
+		/// Block before the one the fast-track vote of each DAO started in and the DAO's total
+		/// shares then, which its support is weighed against like the votes on a proposal.
+		PauseSnapshot get(fn pause_snapshot): map hasher(twox_64_concat) DaoId => Option<(T::BlockNumber, Shares)>;
+
+		/// Sum of the shares supporting the fast-track vote of each DAO.
+		PauseSupportTally get(fn pause_support_tally): map hasher(twox_64_concat) DaoId => Shares;
+
+		/// Vesting schedules of unprocessed funding proposals, and of passed ones whose payment
+		/// waits in the timelock.
+		ProposalVesting get(fn proposal_vesting):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId
+			=> Option<VestingSchedule<T::BlockNumber>>;
+
+		/// Grants vesting to each recipient with the proposal that paid them, at most `MaxGrants`,
+		/// until they are fully claimed or the recipient is kicked from the guild.
+		Grants get(fn grants):
+			double_map hasher(twox_64_concat) DaoId, hasher(blake2_128_concat) T::AccountId
+			=> Vec<(ProposalId, Grant<BalanceOf<T>, T::BlockNumber>)>;
+
+		/// Milestones of unprocessed milestone grant proposals, and of passed ones whose payment
+		/// waits in the timelock.
+		ProposalMilestones get(fn proposal_milestones):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId
+			=> Vec<Milestone<BalanceOf<T>, T::Hash>>;
+
+		/// Milestone grants with unpaid tranches, by the proposal that made them.
+		MilestoneGrants get(fn milestone_grant):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId
+			=> Option<MilestoneGrant<T::AccountId, BalanceOf<T>, T::Hash>>;
+
+		/// Shares of each member supporting approval of the next milestone of each grant that
+		/// count on the vote, as since lowered to what they still hold.
+		MilestoneSupport get(fn milestone_support):
+			double_map hasher(twox_64_concat) (DaoId, ProposalId), hasher(blake2_128_concat) T::AccountId
+			=> Shares;
+
+		/// Block before the one the vote on the next milestone of each grant started in and the
+		/// DAO's total shares then, which its support is weighed against.
+		MilestoneSnapshot get(fn milestone_snapshot):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId
+			=> Option<(T::BlockNumber, Shares)>;
+
+		/// Grants whose next milestone each member supports approving, at most
+		/// `MaxMilestoneApprovals`. May still list grants whose milestone was paid or which were
+		/// cancelled since.
+		MilestonesSupported get(fn milestones_supported):
+			double_map hasher(twox_64_concat) DaoId, hasher(blake2_128_concat) T::AccountId
+			=> Vec<ProposalId>;
+
+		/// Guardians that approved cancelling each payment or action in the timelock.
+		CancelApprovals get(fn cancel_approvals):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId => Vec<T::AccountId>;
+
+		/// Shares each member committed to quadratic votes, by proposal. Entries of processed
+		/// proposals are dropped when the member next commits shares.
+		Commitments get(fn commitments):
+			double_map hasher(twox_64_concat) DaoId, hasher(blake2_128_concat) T::AccountId
+			=> Vec<(ProposalId, Shares)>;
+
+		/// Shares committed to quadratic votes on each proposal, which count towards its quorum.
+		CommittedShares get(fn committed_shares):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId => Shares;
+
+		/// Members with an unprocessed guild kick proposal against them.
+		ProposedToKick get(fn proposed_to_kick):
+			double_map hasher(twox_64_concat) DaoId, hasher(blake2_128_concat) T::AccountId => bool;
+
+		/// Funds belonging to the members of the guild.
+		GuildBalance get(fn guild_balance): map hasher(twox_64_concat) DaoId => BalanceOf<T>;
+
+		/// Tribute held for proposals that are not processed yet.
+		EscrowBalance get(fn escrow_balance): map hasher(twox_64_concat) DaoId => BalanceOf<T>;
+
+		/// Payments of grants that their recipients have not claimed yet.
+		VestingBalance get(fn vesting_balance): map hasher(twox_64_concat) DaoId => BalanceOf<T>;
+
+		/// Tranches of milestone grants reserved until their milestones are approved.
+		MilestoneBalance get(fn milestone_balance): map hasher(twox_64_concat) DaoId => BalanceOf<T>;
+
+		/// Funds that accounts may withdraw from the guild bank.
+		UserBalances get(fn user_balance):
+			double_map hasher(twox_64_concat) DaoId, hasher(blake2_128_concat) T::AccountId => BalanceOf<T>;
+
+		/// Calls dispatched by action proposals if they pass, until they are processed.
+		ProposalActions get(fn proposal_action):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId
+			=> Option<<T as Trait>::Proposal>;
+
+		/// Parameters of the DAO when each unprocessed proposal was submitted, which apply to the
+		/// proposal until it is processed.
+		ProposalParams get(fn proposal_params):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId
+			=> Option<DaoParams<T::BlockNumber, BalanceOf<T>>>;
+
+		/// Parameters adopted by parameter-change proposals if they pass, until they are processed.
+		ParamChanges get(fn param_change):
+			double_map hasher(twox_64_concat) DaoId, hasher(twox_64_concat) ProposalId
+			=> Option<DaoParams<T::BlockNumber, BalanceOf<T>>>;
+
+		/// Period of each member's latest vote and the number of votes they submitted in it.
+		VotesInPeriod:
+			double_map hasher(twox_64_concat) DaoId, hasher(blake2_128_concat) T::AccountId => (u64, u32);
+	}
+	add_extra_genesis {
+		/// Summoning members of the genesis DAO and their shares.
+		config(members): Vec<(T::AccountId, Shares)>;
+		build(|config: &GenesisConfig<T>| {
+			let _ = T::Currency::make_free_balance_be(
+				&<Module<T>>::account_id(GENESIS_DAO),
+				T::Currency::minimum_balance(),
+			);
+			<Module<T>>::create_dao(<Module<T>>::genesis_params(), &config.members);
+		});
 	}
 }
 
 // The pallet's events
 decl_event!(
-	pub enum Event<T> where AccountId = <T as system::Trait>::AccountId {
-		/// Just a dummy event.
-		/// Event `Something` is declared with a parameter of the type `u32` and `AccountId`
-		/// To emit this event, we call the deposit function, from our runtime functions
-		SomethingStored(u32, AccountId),
+	pub enum Event<T> where
+		AccountId = <T as system::Trait>::AccountId,
+		Balance = BalanceOf<T>
+	{
+		/// A DAO was summoned. \[dao, summoner\]
+		Summoned(DaoId, AccountId),
+		/// A proposal was submitted. \[dao, id, proposer, applicant\]
+		ProposalSubmitted(DaoId, ProposalId, AccountId, AccountId),
+		/// A proposal was sponsored into the queue. \[dao, id, index, sponsor, starting period\]
+		ProposalSponsored(DaoId, ProposalId, ProposalIndex, AccountId, u64),
+		/// A member voted on a proposal. \[dao, index, member, vote\]
+		VoteSubmitted(DaoId, ProposalIndex, AccountId, Vote),
+		/// A member changed their vote on a proposal. \[dao, index, member, new vote\]
+		VoteChanged(DaoId, ProposalIndex, AccountId, Vote),
+		/// A proposal was processed. \[dao, index, id, passed\]
+		ProposalProcessed(DaoId, ProposalIndex, ProposalId, bool),
+		/// A guild kick proposal was processed. \[dao, index, id, passed\]
+		GuildKickProcessed(DaoId, ProposalIndex, ProposalId, bool),
+		/// A proposal was cancelled by its proposer. \[dao, id, proposer\]
+		ProposalCancelled(DaoId, ProposalId, AccountId),
+		/// A member burned shares and loot for their fair share of the guild bank.
+		/// \[dao, member, shares, loot\]
+		Ragequit(DaoId, AccountId, Shares, Shares),
+		/// A member changed their delegate key. \[dao, member, delegate key\]
+		DelegateKeyUpdated(DaoId, AccountId, AccountId),
+		/// Funds were withdrawn from the guild bank. \[dao, account, amount\]
+		Withdrawn(DaoId, AccountId, Balance),
+		/// The call of a passed action proposal was dispatched. \[dao, id, result\]
+		ActionExecuted(DaoId, ProposalId, DispatchResult),
+		/// A passed parameter-change proposal changed the DAO's parameters. \[dao, id\]
+		ParamsChanged(DaoId, ProposalId),
+		/// A member delegated their voting weight. \[dao, member, delegate\]
+		VotesDelegated(DaoId, AccountId, AccountId),
+		/// A member took back their voting weight from their delegate. \[dao, member\]
+		DelegationRevoked(DaoId, AccountId),
+		/// A member committed to a secret vote on a proposal. \[dao, index, member\]
+		VoteCommitted(DaoId, ProposalIndex, AccountId),
+		/// The reveal deposit of a vote that was never revealed went to the guild bank.
+		/// \[dao, index, member, deposit\]
+		UnrevealedVotePunished(DaoId, ProposalIndex, AccountId, Balance),
+		/// The payment or action of a passed proposal entered the timelock. \[dao, id, ready at\]
+		Timelocked(DaoId, ProposalId, u64),
+		/// The payment or action of a proposal left the timelock and was executed. \[dao, id\]
+		TimelockExecuted(DaoId, ProposalId),
+		/// A guardian approved cancelling a payment or action in the timelock.
+		/// \[dao, id, guardian\]
+		CancellationApproved(DaoId, ProposalId, AccountId),
+		/// Guardians cancelled a payment or action in the timelock. \[dao, id\]
+		TimelockCancelled(DaoId, ProposalId),
+		/// A DAO set its guardians. \[dao\]
+		GuardiansSet(DaoId),
+		/// A guardian approved vetoing a proposal. \[dao, index, guardian\]
+		VetoApproved(DaoId, ProposalIndex, AccountId),
+		/// Guardians vetoed a proposal, which members vote on ratifying.
+		/// \[dao, index, ratification vote ends\]
+		Vetoed(DaoId, ProposalIndex, u64),
+		/// A member voted on ratifying a veto. \[dao, index, member, ratify\]
+		RatificationVoteSubmitted(DaoId, ProposalIndex, AccountId, bool),
+		/// The ratification vote on a veto decided when its proposal was processed.
+		/// \[dao, index, ratified\]
+		VetoDecided(DaoId, ProposalIndex, bool),
+		/// A DAO was paused. \[dao\]
+		Paused(DaoId),
+		/// A paused DAO was unpaused. \[dao\]
+		Unpaused(DaoId),
+		/// A guardian approved unpausing a DAO. \[dao, guardian\]
+		UnpauseApproved(DaoId, AccountId),
+		/// A member supported the fast-track vote to pause or unpause a DAO. \[dao, member\]
+		PauseToggleSupported(DaoId, AccountId),
+		/// The payment of a passed proposal started vesting. \[dao, id, recipient, amount\]
+		GrantStarted(DaoId, ProposalId, AccountId, Balance),
+		/// A recipient claimed the vested part of a grant. \[dao, id, recipient, amount\]
+		VestedClaimed(DaoId, ProposalId, AccountId, Balance),
+		/// The unvested or unpaid part of a grant the DAO revoked or whose recipient was kicked
+		/// went back to the guild bank.
+		/// \[dao, id, recipient, amount\]
+		GrantRevoked(DaoId, ProposalId, AccountId, Balance),
+		/// The tranche of an approved milestone, or the first one of a passed milestone grant, was
+		/// paid. \[dao, id, milestone, amount\]
+		TranchePaid(DaoId, ProposalId, u32, Balance),
+		/// A member supported approving the next milestone of a grant. \[dao, id, member\]
+		MilestoneApprovalSupported(DaoId, ProposalId, AccountId),
+		/// A member withdrew their support for approving the next milestone of a grant.
+		/// \[dao, id, member\]
+		MilestoneApprovalRevoked(DaoId, ProposalId, AccountId),
+		/// A milestone grant was cancelled and its unpaid tranches went back to the guild bank.
+		/// \[dao, id, amount\]
+		MilestoneGrantCancelled(DaoId, ProposalId, Balance),
 	}
 );
 
 // The pallet's errors
 decl_error! {
 	pub enum Error for Module<T: Trait> {
-		/// Value was None
-		NoneValue,
-		/// Value reached maximum and cannot be incremented further
-		StorageOverflow,
+		/// The sender is not the delegate key of a member with shares.
+		NotDelegate,
+		/// The sender is not a member.
+		NotMember,
+		/// The proposal does not exist.
+		UnknownProposal,
+		/// No proposal has been sponsored at this index.
+		UnknownProposalIndex,
+		/// The applicant has been kicked from the guild.
+		ApplicantJailed,
+		/// The account is not a member with shares or loot.
+		NotKickable,
+		/// A guild kick proposal against the member is already pending.
+		AlreadyProposedToKick,
+		/// The member has not been kicked from the guild.
+		NotJailed,
+		/// The proposal has already been sponsored.
+		AlreadySponsored,
+		/// The proposal has been cancelled.
+		ProposalCancelled,
+		/// Only the proposer may do this.
+		NotProposer,
+		/// Voting on the proposal has not started yet.
+		VotingNotStarted,
+		/// Voting on the proposal has ended.
+		VotingPeriodExpired,
+		/// The member has already cast this vote on the proposal.
+		AlreadyVoted,
+		/// The proposal has already been processed.
+		AlreadyProcessed,
+		/// The proposal's voting and grace periods have not ended yet.
+		ProposalNotReady,
+		/// The previous proposal in the queue must be processed first.
+		PreviousProposalUnprocessed,
+		/// Not enough shares or loot to burn.
+		InsufficientShares,
+		/// A proposal the member voted yes on has not been processed yet.
+		CannotRagequitYet,
+		/// The internal balance is too low.
+		InsufficientBalance,
+		/// The delegate key is already used by a member.
+		DelegateKeyInUse,
+		/// Shares or loot would overflow.
+		SharesOverflow,
+		/// The member has submitted too many votes this period.
+		TooManyVotes,
+		/// The action's weight exceeds `MaxActionWeight`.
+		ActionTooHeavy,
+		/// The DAO does not exist.
+		UnknownDao,
+		/// The DAO's parameters are invalid.
+		InvalidParams,
+		/// A DAO must be summoned with at least one member, each holding shares.
+		NoSummoners,
+		/// Voting weight can only be delegated to a member holding shares.
+		DelegateNotMember,
+		/// The delegation would lead the member's voting weight back to them.
+		DelegationCycle,
+		/// The delegation would make the member's voting weight follow more than
+		/// `MaxDelegationDepth` delegations.
+		DelegationTooDeep,
+		/// The delegate already has `MaxDelegators` delegators.
+		TooManyDelegators,
+		/// The member does not delegate their voting weight.
+		NotDelegating,
+		/// The proposal is decided in the other voting mode.
+		WrongVotingMode,
+		/// The shares are committed to quadratic votes on unprocessed proposals.
+		SharesLocked,
+		/// The member has committed shares to `MaxCommitments` unprocessed proposals.
+		TooManyCommitments,
+		/// The conviction of a vote is set when it is first cast.
+		ConvictionFixed,
+		/// Votes on the proposal are secret and must be committed, then revealed.
+		SecretBallot,
+		/// Votes on the proposal are not secret.
+		NotSecretBallot,
+		/// The proposal's reveal period is not running.
+		NotRevealPeriod,
+		/// The member has not committed to a vote on the proposal.
+		NoCommitment,
+		/// The vote and salt do not match the member's commitment.
+		InvalidReveal,
+		/// The committed vote may still be revealed.
+		RevealNotOver,
+		/// Nothing of the proposal waits in the timelock.
+		NotTimelocked,
+		/// The proposal's timelock has not passed yet.
+		TimelockNotOver,
+		/// The proposal's timelock has passed, so it can no longer be cancelled.
+		TimelockOver,
+		/// The sender is not a guardian of the DAO.
+		NotGuardian,
+		/// The guardian already approved the cancellation.
+		AlreadyApproved,
+		/// A DAO may have at most `MaxGuardians` guardians.
+		TooManyGuardians,
+		/// The proposal is not in its grace period.
+		NotGracePeriod,
+		/// The proposal was already vetoed.
+		AlreadyVetoed,
+		/// The proposal was not vetoed.
+		NotVetoed,
+		/// The proposal would not pass, so there is nothing to veto.
+		NotPassing,
+		/// The ratification vote on the veto is over.
+		RatificationOver,
+		/// The DAO is paused, only ragequit, withdrawals and votes are open.
+		DaoPaused,
+		/// The DAO is already paused.
+		AlreadyPaused,
+		/// The DAO is not paused.
+		NotPaused,
+		/// A vesting schedule needs a duration no shorter than its cliff, and a payment to vest.
+		InvalidSchedule,
+		/// The recipient already holds `MaxGrants` grants from the DAO.
+		TooManyGrants,
+		/// The grant does not exist.
+		UnknownGrant,
+		/// Nothing of the grant has vested that was not claimed yet.
+		NothingVested,
+		/// A milestone grant needs between one and `MaxMilestones` milestones, each with a tranche.
+		InvalidMilestones,
+		/// The milestone grant does not exist or has been paid in full.
+		UnknownMilestoneGrant,
+		/// The member supports approving the next milestone of `MaxMilestoneApprovals` grants.
+		TooManyMilestoneApprovals,
+		/// The member does not support approving the grant's next milestone.
+		NoMilestoneSupport,
 	}
 }
 
 // The pallet's dispatchable functions.
 decl_module! {
 	/// The module declaration.
-	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
-		// Initializing errors
-		// this includes information about your errors in the node's metadata.
-		// it is needed only if you are using errors in your pallet
+	pub struct Module<T: Trait> for enum Call where origin: <T as system::Trait>::Origin {
 		type Error = Error<T>;
 
-		// Initializing events
-		// this is needed only if you are using events in your pallet
 		fn deposit_event() = default;
 
-		/// Just a dummy entry point.
-		/// function that can be called by the external world as an extrinsics call
-		/// takes a parameter of the type `AccountId`, stores it, and emits an event
-		#[weight = 10_000]
-		pub fn do_something(origin, something: u32) -> dispatch::DispatchResult {
-			// Check it was signed and get the signer. See also: ensure_root and ensure_none
+		/// Number of blocks in a period of the genesis DAO.
+		const PeriodDuration: T::BlockNumber = T::PeriodDuration::get();
+
+		/// Number of periods a sponsored proposal of the genesis DAO is open for voting.
+		const VotingPeriodLength: u64 = T::VotingPeriodLength::get();
+
+		/// Number of periods between the end of voting and processing in the genesis DAO.
+		const GracePeriodLength: u64 = T::GracePeriodLength::get();
+
+		/// Deposit reserved from the sponsor of a proposal to the genesis DAO.
+		const ProposalDeposit: BalanceOf<T> = T::ProposalDeposit::get();
+
+		/// Part of the deposit paid to the processor of a proposal to the genesis DAO.
+		const ProcessingReward: BalanceOf<T> = T::ProcessingReward::get();
+
+		/// Maximum dilution of yes voters before a proposal to the genesis DAO fails.
+		const DilutionBound: u128 = T::DilutionBound::get();
+
+		/// Number of votes per member and period whose fee is refunded.
+		const MaxVotesPerPeriod: u32 = T::MaxVotesPerPeriod::get();
+
+		/// Maximum weight of the call of an action proposal.
+		const MaxActionWeight: Weight = T::MaxActionWeight::get();
+
+		/// Maximum number of delegations voting weight may follow.
+		const MaxDelegationDepth: u32 = T::MaxDelegationDepth::get();
+
+		/// Maximum number of members delegating to the same member.
+		const MaxDelegators: u32 = T::MaxDelegators::get();
+
+		/// Maximum number of unprocessed proposals a member may commit shares to.
+		const MaxCommitments: u32 = T::MaxCommitments::get();
+
+		/// Maximum number of guardians of a DAO.
+		const MaxGuardians: u32 = T::MaxGuardians::get();
+
+		/// Maximum number of vesting grants an account may hold from the same DAO at once.
+		const MaxGrants: u32 = T::MaxGrants::get();
+
+		/// Maximum number of milestones of a milestone grant.
+		const MaxMilestones: u32 = T::MaxMilestones::get();
+
+		/// Maximum number of milestone grants a member may support approving at once.
+		const MaxMilestoneApprovals: u32 = T::MaxMilestoneApprovals::get();
+
+		/// Maximum number of share checkpoints kept per member.
+		const MaxCheckpoints: u32 = T::MaxCheckpoints::get();
+
+		fn on_runtime_upgrade() -> Weight {
+			migration::migrate::<T>()
+		}
+
+		fn on_finalize(_n: T::BlockNumber) {
+			#[cfg(any(test, all(feature = "try-runtime", debug_assertions)))]
+			{
+				if let Err(e) = Self::do_try_state() {
+					panic!("DAO invariant violated: {}", e);
+				}
+			}
+		}
+
+		/// Summon a new DAO with `params`, giving `summoners` their shares.
+		///
+		/// The sender endows the new DAO's guild bank with the existential deposit.
+		#[weight = 100_000
+			+ T::DbWeight::get().reads_writes(3 + 3 * summoners.len() as Weight, 6 + 4 * summoners.len() as Weight)]
+		pub fn summon(
+			origin,
+			params: DaoParams<T::BlockNumber, BalanceOf<T>>,
+			summoners: Vec<(T::AccountId, Shares)>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_valid_params(&params)?;
+			ensure!(
+				!summoners.is_empty() && summoners.iter().all(|(_, shares)| *shares > 0),
+				Error::<T>::NoSummoners
+			);
+			summoners.iter()
+				.try_fold(0 as Shares, |total, (_, shares)| total.checked_add(*shares))
+				.ok_or(Error::<T>::SharesOverflow)?;
+
+			let dao_id = Self::dao_count();
+			T::Currency::transfer(
+				&who,
+				&Self::account_id(dao_id),
+				T::Currency::minimum_balance(),
+				ExistenceRequirement::KeepAlive,
+			)?;
+			Self::create_dao(params, &summoners);
+
+			Self::deposit_event(RawEvent::Summoned(dao_id, who));
+			Ok(())
+		}
+
+		/// Propose that `applicant` receives shares, loot and a payment from the guild bank in
+		/// exchange for `tribute_offered`, which is moved into escrow until processing.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(5, 6)]
+		pub fn submit_proposal(
+			origin,
+			dao_id: DaoId,
+			applicant: T::AccountId,
+			shares_requested: Shares,
+			loot_requested: Shares,
+			tribute_offered: BalanceOf<T>,
+			payment_requested: BalanceOf<T>,
+			details: Vec<u8>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let params = Self::params(dao_id)?;
+			Self::ensure_not_paused(dao_id)?;
+			ensure!(!Self::is_jailed(dao_id, &applicant), Error::<T>::ApplicantJailed);
+			shares_requested.checked_add(loot_requested).ok_or(Error::<T>::SharesOverflow)?;
+
+			T::Currency::transfer(&who, &Self::account_id(dao_id), tribute_offered, ExistenceRequirement::KeepAlive)?;
+			<EscrowBalance<T>>::mutate(dao_id, |b| *b = b.saturating_add(tribute_offered));
+
+			Self::new_proposal(dao_id, params, Proposal {
+				applicant,
+				proposer: who,
+				shares_requested,
+				loot_requested,
+				tribute_offered,
+				payment_requested,
+				details,
+				..Default::default()
+			});
+			Ok(())
+		}
+
+		/// Propose that `applicant` receives a grant of `payment_requested` from the guild bank,
+		/// vesting on `schedule`, in exchange for `tribute_offered`.
+		///
+		/// Once the proposal passes, or its timelock has passed, the payment moves out of the
+		/// guild balance and vests from that block on. The applicant claims the vested part with
+		/// `claim_vested`. If they are kicked from the guild, they are credited what has vested
+		/// and the rest returns to the guild balance.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(5, 7)]
+		pub fn submit_vesting_proposal(
+			origin,
+			dao_id: DaoId,
+			applicant: T::AccountId,
+			tribute_offered: BalanceOf<T>,
+			payment_requested: BalanceOf<T>,
+			schedule: VestingSchedule<T::BlockNumber>,
+			details: Vec<u8>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let params = Self::params(dao_id)?;
+			Self::ensure_not_paused(dao_id)?;
+			ensure!(!Self::is_jailed(dao_id, &applicant), Error::<T>::ApplicantJailed);
+			ensure!(
+				!payment_requested.is_zero() && !schedule.duration.is_zero() && schedule.cliff <= schedule.duration,
+				Error::<T>::InvalidSchedule
+			);
+
+			T::Currency::transfer(&who, &Self::account_id(dao_id), tribute_offered, ExistenceRequirement::KeepAlive)?;
+			<EscrowBalance<T>>::mutate(dao_id, |b| *b = b.saturating_add(tribute_offered));
+
+			let proposal_id = Self::new_proposal(dao_id, params, Proposal {
+				applicant,
+				proposer: who,
+				tribute_offered,
+				payment_requested,
+				details,
+				..Default::default()
+			});
+			<ProposalVesting<T>>::insert(dao_id, proposal_id, schedule);
+			Ok(())
+		}
+
+		/// Propose that `applicant` receives a grant paid in tranches, one for each of
+		/// `milestones`, in exchange for `tribute_offered`.
+		///
+		/// The proposal asks for the sum of the tranches. Once it passes, or its timelock has
+		/// passed, the first tranche is paid and the rest stays reserved in the guild bank. Each
+		/// later tranche is paid once members holding more than half of the shares support
+		/// approving its milestone with `approve_milestone`. The DAO can cancel the grant with
+		/// `cancel_milestone_grant`, returning the unpaid tranches to the guild balance.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(5, 7)]
+		pub fn submit_milestone_proposal(
+			origin,
+			dao_id: DaoId,
+			applicant: T::AccountId,
+			tribute_offered: BalanceOf<T>,
+			milestones: Vec<Milestone<BalanceOf<T>, T::Hash>>,
+			details: Vec<u8>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let params = Self::params(dao_id)?;
+			Self::ensure_not_paused(dao_id)?;
+			ensure!(!Self::is_jailed(dao_id, &applicant), Error::<T>::ApplicantJailed);
+			ensure!(
+				!milestones.is_empty()
+					&& milestones.len() <= T::MaxMilestones::get() as usize
+					&& milestones.iter().all(|milestone| !milestone.amount.is_zero()),
+				Error::<T>::InvalidMilestones
+			);
+			let payment_requested = milestones.iter()
+				.try_fold(BalanceOf::<T>::zero(), |total, milestone| total.checked_add(&milestone.amount))
+				.ok_or(Error::<T>::InvalidMilestones)?;
+
+			T::Currency::transfer(&who, &Self::account_id(dao_id), tribute_offered, ExistenceRequirement::KeepAlive)?;
+			<EscrowBalance<T>>::mutate(dao_id, |b| *b = b.saturating_add(tribute_offered));
+
+			let proposal_id = Self::new_proposal(dao_id, params, Proposal {
+				applicant,
+				proposer: who,
+				tribute_offered,
+				payment_requested,
+				details,
+				..Default::default()
+			});
+			<ProposalMilestones<T>>::insert(dao_id, proposal_id, milestones);
+			Ok(())
+		}
+
+		/// Propose to kick `member_to_kick` from the guild, converting their shares to loot.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(3, 4)]
+		pub fn submit_guild_kick_proposal(
+			origin,
+			dao_id: DaoId,
+			member_to_kick: T::AccountId,
+			details: Vec<u8>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let params = Self::params(dao_id)?;
+			Self::ensure_not_paused(dao_id)?;
+			let member = Self::members(dao_id, &member_to_kick).ok_or(Error::<T>::NotKickable)?;
+			ensure!(member.shares > 0 || member.loot > 0, Error::<T>::NotKickable);
+			ensure!(!member.jailed, Error::<T>::ApplicantJailed);
+			ensure!(!Self::proposed_to_kick(dao_id, &member_to_kick), Error::<T>::AlreadyProposedToKick);
+
+			<ProposedToKick<T>>::insert(dao_id, &member_to_kick, true);
+			Self::new_proposal(dao_id, params, Proposal {
+				applicant: member_to_kick,
+				proposer: who,
+				guild_kick: true,
+				details,
+				..Default::default()
+			});
+			Ok(())
+		}
+
+		/// Propose that the DAO dispatches `action` with its own origin.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(3, 4)]
+		pub fn submit_action_proposal(
+			origin,
+			dao_id: DaoId,
+			action: Box<<T as Trait>::Proposal>,
+			details: Vec<u8>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let params = Self::params(dao_id)?;
+			Self::ensure_not_paused(dao_id)?;
+			ensure!(!Self::is_jailed(dao_id, &who), Error::<T>::ApplicantJailed);
+			ensure!(
+				action.get_dispatch_info().weight <= T::MaxActionWeight::get(),
+				Error::<T>::ActionTooHeavy
+			);
+
+			let proposal_id = Self::new_proposal(dao_id, params, Proposal {
+				applicant: who.clone(),
+				proposer: who,
+				details,
+				..Default::default()
+			});
+			<ProposalActions<T>>::insert(dao_id, proposal_id, *action);
+			Ok(())
+		}
+
+		/// Propose that the DAO adopts `new_params`, which apply to proposals submitted after
+		/// this one passes.
+		///
+		/// The period duration cannot change, as the DAO's current period is derived from it.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(3, 4)]
+		pub fn submit_params_proposal(
+			origin,
+			dao_id: DaoId,
+			new_params: DaoParams<T::BlockNumber, BalanceOf<T>>,
+			details: Vec<u8>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let params = Self::params(dao_id)?;
+			Self::ensure_not_paused(dao_id)?;
+			ensure!(!Self::is_jailed(dao_id, &who), Error::<T>::ApplicantJailed);
+			Self::ensure_valid_params(&new_params)?;
+			ensure!(new_params.period_duration == params.period_duration, Error::<T>::InvalidParams);
+
+			let proposal_id = Self::new_proposal(dao_id, params, Proposal {
+				applicant: who.clone(),
+				proposer: who,
+				details,
+				..Default::default()
+			});
+			<ParamChanges<T>>::insert(dao_id, proposal_id, new_params);
+			Ok(())
+		}
+
+		/// Sponsor a submitted proposal into the queue, reserving the proposal deposit.
+		///
+		/// Must be called with the delegate key of a member holding shares.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(7, 5)]
+		pub fn sponsor_proposal(origin, dao_id: DaoId, proposal_id: ProposalId) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::params(dao_id)?;
+			Self::ensure_not_paused(dao_id)?;
+			Self::ensure_delegate(dao_id, &who)?;
+			let mut proposal = Self::proposals(dao_id, proposal_id).ok_or(Error::<T>::UnknownProposal)?;
+			ensure!(!proposal.sponsored, Error::<T>::AlreadySponsored);
+			ensure!(!proposal.cancelled, Error::<T>::ProposalCancelled);
+			let params = Self::params_of(dao_id, proposal_id)?;
+			ensure!(!Self::is_jailed(dao_id, &proposal.applicant), Error::<T>::ApplicantJailed);
 
-			// Code to execute when something calls this.
-			// For example: the following line stores the passed in u32 in the storage
-			Something::put(something);
+			T::Currency::reserve(&who, params.proposal_deposit)?;
 
-			// Here we are raising the Something event
-			Self::deposit_event(RawEvent::SomethingStored(something, who));
+			let index = Self::proposal_queue_length(dao_id);
+			let last_starting_period = index.checked_sub(1)
+				.and_then(|i| Self::proposal_queue(dao_id, i))
+				.and_then(|id| Self::proposals(dao_id, id))
+				.map_or(0, |p| p.starting_period);
+			let starting_period = Self::current_period(dao_id).max(last_starting_period) + 1;
+
+			proposal.sponsor = Some(who.clone());
+			proposal.sponsored = true;
+			proposal.starting_period = starting_period;
+			<Proposals<T>>::insert(dao_id, proposal_id, proposal);
+			ProposalQueue::insert(dao_id, index, proposal_id);
+			ProposalQueueLength::insert(dao_id, index + 1);
+			// Shares count as held at the end of the previous block, so that none can be moved
+			// into place in the block of the sponsorship.
+			<ProposalSnapshots<T>>::insert(
+				dao_id,
+				proposal_id,
+				(<system::Module<T>>::block_number().saturating_sub(One::one()), Self::total_shares(dao_id)),
+			);
+
+			Self::deposit_event(RawEvent::ProposalSponsored(dao_id, proposal_id, index, who, starting_period));
 			Ok(())
 		}
 
-		/// Another dummy entry point.
-		/// takes no parameters, attempts to increment storage value, and possibly throws an error
-		#[weight = 10_000]
-		pub fn cause_error(origin) -> dispatch::DispatchResult {
-			// Check it was signed and get the signer. See also: ensure_root and ensure_none
-			let _who = ensure_signed(origin)?;
+		/// Vote on the sponsored proposal at `proposal_index` with all of the member's shares and
+		/// the voting weight delegated to them.
+		///
+		/// Shares count as held in the block the proposal was sponsored in, so shares received
+		/// later do not count, and as far as the member still holds them, so shares that left
+		/// do not count either.
+		///
+		/// Delegated weight is counted when the member first votes on the proposal. It comes from
+		/// every member whose delegations lead to the voter within `MaxDelegationDepth` steps and
+		/// who did not vote on the proposal themselves. Voting overrides delegation: the shares of
+		/// a member counted by their delegate, and those delegated through them, move to the
+		/// member's own vote.
+		///
+		/// A member may change their vote while voting is open, which moves their ballot with the
+		/// weight it was cast with to the new vote. Taking back the yes vote that kept the member
+		/// from ragequitting looks back through the unprocessed proposals before it for another
+		/// yes vote.
+		///
+		/// Must be called with the delegate key of a member holding shares. The fee of the
+		/// member's first `MaxVotesPerPeriod` votes in a period is refunded, the weight includes
+		/// counting them and is refunded for delegators that were not visited.
+		#[weight = Module::<T>::vote_weight(Module::<T>::max_delegators_visited())]
+		pub fn submit_vote(
+			origin,
+			dao_id: DaoId,
+			proposal_index: ProposalIndex,
+			vote: Vote,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let (visited, pays_fee) = Self::do_vote(&who, dao_id, proposal_index, vote, Conviction::None)?;
+			Ok(PostDispatchInfo { actual_weight: Some(Self::vote_weight(visited)), pays_fee })
+		}
+
+		/// Vote like `submit_vote`, multiplying the weight of the member's own shares by
+		/// `conviction` in return for locking them against ragequit after voting ends.
+		///
+		/// The conviction's weight counts towards approval but not quorum. Conviction is set when
+		/// the vote is first cast, changing the vote keeps it. Locks of several votes do not add
+		/// up, the latest end and largest amount apply.
+		#[weight = Module::<T>::vote_weight(Module::<T>::max_delegators_visited())
+			+ T::DbWeight::get().reads_writes(2, 3)]
+		pub fn submit_conviction_vote(
+			origin,
+			dao_id: DaoId,
+			proposal_index: ProposalIndex,
+			vote: Vote,
+			conviction: Conviction,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let (visited, pays_fee) = Self::do_vote(&who, dao_id, proposal_index, vote, conviction)?;
+			Ok(PostDispatchInfo {
+				actual_weight: Some(Self::vote_weight(visited) + T::DbWeight::get().reads_writes(2, 3)),
+				pays_fee,
+			})
+		}
+
+		/// Vote on the sponsored proposal at `proposal_index` of a DAO in quadratic mode,
+		/// committing `shares` to the vote, which weighs their integer square root.
+		///
+		/// Committed shares are locked until the proposal is processed, and the shares a member
+		/// commits to unprocessed proposals add up to at most their shares, and at most the shares
+		/// they held when the proposal was sponsored go to each vote. Changing the vote
+		/// while voting is open replaces the commitment with `shares`.
+		///
+		/// Must be called with the delegate key of a member holding shares. The fee of the
+		/// member's first `MaxVotesPerPeriod` votes in a period is refunded, the weight includes
+		/// counting them.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(14 + T::MaxCommitments::get() as Weight, 7)]
+		pub fn submit_quadratic_vote(
+			origin,
+			dao_id: DaoId,
+			proposal_index: ProposalIndex,
+			vote: Vote,
+			shares: Shares,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let (member_address, member, proposal_id, mut proposal) =
+				Self::ensure_can_vote(dao_id, &who, proposal_index, vote, VotingMode::Quadratic)?;
+			let previous = Self::vote_of((dao_id, proposal_id), &member_address);
+
+			let mut commitments = Self::open_commitments(dao_id, &member_address);
+			let previous_commitment = commitments.iter()
+				.find(|(id, _)| *id == proposal_id)
+				.map_or(0, |(_, committed)| *committed);
+			commitments.retain(|(id, _)| *id != proposal_id);
+			let committed = commitments.iter().fold(0 as Shares, |total, (_, committed)| total.saturating_add(*committed));
+			ensure!(committed.saturating_add(shares) <= member.shares, Error::<T>::SharesLocked);
+			ensure!(
+				shares <= Self::voting_shares(dao_id, proposal_id, &member_address, &member),
+				Error::<T>::InsufficientShares
+			);
+			ensure!((commitments.len() as u32) < T::MaxCommitments::get(), Error::<T>::TooManyCommitments);
+
+			if let Some(previous) = previous {
+				let tally = Self::tally_mut(&mut proposal, previous.vote);
+				*tally = tally.saturating_sub(previous.shares);
+			}
+			commitments.push((proposal_id, shares));
+			<Commitments<T>>::insert(dao_id, &member_address, commitments);
+			<CommittedShares<T>>::mutate(dao_id, proposal_id, |total| {
+				*total = total.saturating_sub(previous_commitment).saturating_add(shares)
+			});
+
+			let ballot = Ballot { vote, shares: shares.integer_sqrt() };
+			let pays_fee = Self::count_vote(dao_id, &member_address);
+			Self::record_vote(
+				dao_id, proposal_index, proposal_id, proposal, member_address, member, ballot, previous.is_some(),
+			);
+			Ok(PostDispatchInfo { actual_weight: None, pays_fee })
+		}
+
+		/// Process the proposal at `proposal_index` once its voting and grace periods are over.
+		///
+		/// Proposals are processed in queue order. The caller receives the processing reward out
+		/// of the sponsor's deposit. Weighs up to `MaxActionWeight` more to dispatch the call of
+		/// an action proposal, the unused part is refunded.
+		///
+		/// A vetoed proposal waits for the end of the ratification vote and fails if the veto was
+		/// ratified.
+		///
+		/// If the DAO has a timelock, the payment or action of a passed proposal waits in it
+		/// instead, see `execute_timelocked`. Shares, loot, parameter changes and guild kicks
+		/// take effect right away.
+		#[weight = Module::<T>::process_weight(T::MaxActionWeight::get())]
+		pub fn process_proposal(origin, dao_id: DaoId, proposal_index: ProposalIndex) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::params(dao_id)?;
+			Self::ensure_not_paused(dao_id)?;
+			let proposal_id = Self::proposal_queue(dao_id, proposal_index).ok_or(Error::<T>::UnknownProposalIndex)?;
+			let mut proposal = Self::proposals(dao_id, proposal_id).ok_or(Error::<T>::UnknownProposal)?;
+			let params = Self::params_of(dao_id, proposal_id)?;
+			Self::ensure_processable(dao_id, &params, proposal_index, &proposal)?;
+			let ratified = match Self::veto(dao_id, proposal_id) {
+				Some(veto) => {
+					ensure!(Self::current_period(dao_id) >= veto.ratification_ends, Error::<T>::ProposalNotReady);
+					Some(veto.ratify > veto.overturn)
+				}
+				None => None,
+			};
+
+			let kind = Self::kind_of(dao_id, proposal_id, &proposal);
+			let did_pass = ratified != Some(true) && Self::did_pass(dao_id, proposal_id, &params, kind, &proposal);
+			let mut action_weight = 0;
+			if proposal.guild_kick {
+				if did_pass {
+					Self::jail(dao_id, &proposal.applicant);
+				}
+				<ProposedToKick<T>>::remove(dao_id, &proposal.applicant);
+			} else if <ProposalActions<T>>::contains_key(dao_id, proposal_id) {
+				if !did_pass {
+					<ProposalActions<T>>::remove(dao_id, proposal_id);
+				} else if params.timelock_periods > 0 {
+					Self::enter_timelock(dao_id, proposal_id, &params);
+				} else if let Some(action) = <ProposalActions<T>>::take(dao_id, proposal_id) {
+					action_weight = Self::execute(dao_id, proposal_id, action);
+				}
+			} else if let Some(new_params) = <ParamChanges<T>>::take(dao_id, proposal_id) {
+				if did_pass {
+					<Daos<T>>::insert(dao_id, new_params);
+					Self::deposit_event(RawEvent::ParamsChanged(dao_id, proposal_id));
+				}
+			} else if did_pass {
+				Self::mint(dao_id, &proposal.applicant, proposal.shares_requested, proposal.loot_requested);
+				Self::internal_transfer(dao_id, Account::Escrow, Account::Guild, proposal.tribute_offered);
+				if params.timelock_periods > 0 && !proposal.payment_requested.is_zero() {
+					Self::enter_timelock(dao_id, proposal_id, &params);
+				} else {
+					Self::pay(dao_id, proposal_id, &proposal);
+				}
+			} else {
+				<ProposalVesting<T>>::remove(dao_id, proposal_id);
+				<ProposalMilestones<T>>::remove(dao_id, proposal_id);
+				Self::internal_transfer(
+					dao_id,
+					Account::Escrow,
+					Account::User(proposal.proposer.clone()),
+					proposal.tribute_offered,
+				);
+			}
+
+			if let Some(sponsor) = &proposal.sponsor {
+				Self::return_deposit(&params, sponsor, &who);
+			}
+
+			proposal.processed = true;
+			proposal.did_pass = did_pass;
+			let guild_kick = proposal.guild_kick;
+			<Proposals<T>>::insert(dao_id, proposal_id, proposal);
+			ProcessedQueueLength::insert(dao_id, proposal_index + 1);
+			<ProposalParams<T>>::remove(dao_id, proposal_id);
+			<ProposalSnapshots<T>>::remove(dao_id, proposal_id);
+			<VetoApprovals<T>>::remove(dao_id, proposal_id);
+			Vetoes::remove(dao_id, proposal_id);
+			<RatificationVotes<T>>::remove_prefix((dao_id, proposal_id));
+
+			if let Some(ratified) = ratified {
+				Self::deposit_event(RawEvent::VetoDecided(dao_id, proposal_index, ratified));
+			}
+			if guild_kick {
+				Self::deposit_event(RawEvent::GuildKickProcessed(dao_id, proposal_index, proposal_id, did_pass));
+			} else {
+				Self::deposit_event(RawEvent::ProposalProcessed(dao_id, proposal_index, proposal_id, did_pass));
+			}
+			Ok(Some(Self::process_weight(action_weight)).into())
+		}
+
+		/// Cancel an unsponsored proposal, returning its tribute to the proposer's internal
+		/// balance.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(3, 6)]
+		pub fn cancel_proposal(origin, dao_id: DaoId, proposal_id: ProposalId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut proposal = Self::proposals(dao_id, proposal_id).ok_or(Error::<T>::UnknownProposal)?;
+			ensure!(proposal.proposer == who, Error::<T>::NotProposer);
+			ensure!(!proposal.sponsored, Error::<T>::AlreadySponsored);
+			ensure!(!proposal.cancelled, Error::<T>::ProposalCancelled);
 
-			match Something::get() {
-				None => Err(Error::<T>::NoneValue)?,
-				Some(old) => {
-					let new = old.checked_add(1).ok_or(Error::<T>::StorageOverflow)?;
-					Something::put(new);
-					Ok(())
-				},
+			proposal.cancelled = true;
+			if proposal.guild_kick {
+				<ProposedToKick<T>>::remove(dao_id, &proposal.applicant);
 			}
+			<ProposalActions<T>>::remove(dao_id, proposal_id);
+			<ParamChanges<T>>::remove(dao_id, proposal_id);
+			<ProposalParams<T>>::remove(dao_id, proposal_id);
+			<ProposalVesting<T>>::remove(dao_id, proposal_id);
+			<ProposalMilestones<T>>::remove(dao_id, proposal_id);
+			Self::internal_transfer(dao_id, Account::Escrow, Account::User(who.clone()), proposal.tribute_offered);
+			<Proposals<T>>::insert(dao_id, proposal_id, proposal);
+
+			Self::deposit_event(RawEvent::ProposalCancelled(dao_id, proposal_id, who));
+			Ok(())
+		}
+
+		/// Burn shares and loot in exchange for their fair share of the guild bank.
+		///
+		/// Not allowed while a proposal the member voted yes on is still unprocessed, unless the
+		/// DAO is paused after it became due for processing, nor for shares committed to quadratic
+		/// votes on unprocessed proposals or locked by conviction. Burnt shares no longer support
+		/// approving milestones or the fast-track vote.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(
+			23 + T::MaxCommitments::get() as Weight + 2 * T::MaxMilestoneApprovals::get() as Weight,
+			9 + 2 * T::MaxMilestoneApprovals::get() as Weight,
+		)]
+		pub fn ragequit(origin, dao_id: DaoId, shares_to_burn: Shares, loot_to_burn: Shares) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let member = Self::members(dao_id, &who).ok_or(Error::<T>::NotMember)?;
+			ensure!(
+				member.shares >= shares_to_burn && member.loot >= loot_to_burn,
+				Error::<T>::InsufficientShares
+			);
+			let locked = Self::locked_shares(dao_id, &who).max(Self::conviction_locked(dao_id, &who));
+			ensure!(shares_to_burn <= member.shares.saturating_sub(locked), Error::<T>::SharesLocked);
+
+			Self::do_ragequit(dao_id, &who, member, shares_to_burn, loot_to_burn)
+		}
+
+		/// Return the fair share of a kicked member's remaining loot to them.
+		///
+		/// Loot the kicked member's shares turned into stays locked as long as their conviction
+		/// locked the shares, and can be returned by another call once the lock ends.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(11, 5)]
+		pub fn ragekick(origin, dao_id: DaoId, member_to_kick: T::AccountId) -> DispatchResult {
+			ensure_signed(origin)?;
+			let member = Self::members(dao_id, &member_to_kick).ok_or(Error::<T>::NotMember)?;
+			ensure!(member.jailed, Error::<T>::NotJailed);
+			ensure!(member.loot > 0, Error::<T>::InsufficientShares);
+			let loot = member.loot.saturating_sub(Self::conviction_locked(dao_id, &member_to_kick));
+			ensure!(loot > 0, Error::<T>::SharesLocked);
+
+			Self::do_ragequit(dao_id, &member_to_kick, member, 0, loot)
+		}
+
+		/// Withdraw `amount` of the sender's internal balance from the guild bank.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(3, 3)]
+		pub fn withdraw_balance(origin, dao_id: DaoId, amount: BalanceOf<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let balance = Self::user_balance(dao_id, &who);
+			ensure!(balance >= amount, Error::<T>::InsufficientBalance);
+
+			T::Currency::transfer(&Self::account_id(dao_id), &who, amount, ExistenceRequirement::KeepAlive)?;
+			<UserBalances<T>>::insert(dao_id, &who, balance - amount);
+
+			Self::deposit_event(RawEvent::Withdrawn(dao_id, who, amount));
+			Ok(())
 		}
+
+		/// Change the key the sender uses to sponsor and vote.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(4, 3)]
+		pub fn update_delegate_key(origin, dao_id: DaoId, new_delegate_key: T::AccountId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut member = Self::members(dao_id, &who).ok_or(Error::<T>::NotMember)?;
+			ensure!(member.shares > 0, Error::<T>::NotMember);
+
+			if new_delegate_key != who {
+				ensure!(!<Members<T>>::contains_key(dao_id, &new_delegate_key), Error::<T>::DelegateKeyInUse);
+				ensure!(
+					!<MemberByDelegateKey<T>>::contains_key(dao_id, &new_delegate_key),
+					Error::<T>::DelegateKeyInUse
+				);
+			}
+
+			<MemberByDelegateKey<T>>::remove(dao_id, &member.delegate_key);
+			<MemberByDelegateKey<T>>::insert(dao_id, &new_delegate_key, &who);
+			member.delegate_key = new_delegate_key.clone();
+			<Members<T>>::insert(dao_id, &who, member);
+
+			Self::deposit_event(RawEvent::DelegateKeyUpdated(dao_id, who, new_delegate_key));
+			Ok(())
+		}
+
+		/// Delegate the sender's voting weight to the member `delegate`, replacing any previous
+		/// delegation.
+		///
+		/// The delegate's own delegation is followed in turn, as long as the weight of every
+		/// member delegating to the sender, directly or through others, still reaches the end of
+		/// the chain within `MaxDelegationDepth` delegations without coming back to the sender.
+		/// Ballots that counted the sender's shares keep them until the sender, or a member the
+		/// new delegation leads to, votes on the proposal, which moves the shares to that vote.
+		///
+		/// Must be called with the delegate key of a member holding shares.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(
+			5 + T::MaxDelegationDepth::get() as Weight + Module::<T>::max_delegators_visited() as Weight,
+			4,
+		)]
+		pub fn delegate_votes(origin, dao_id: DaoId, delegate: T::AccountId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::params(dao_id)?;
+			let (member_address, _) = Self::ensure_delegate(dao_id, &who)?;
+			ensure!(
+				Self::members(dao_id, &delegate).map_or(false, |m| m.shares > 0),
+				Error::<T>::DelegateNotMember
+			);
+			ensure!(delegate != member_address, Error::<T>::DelegationCycle);
+
+			// The longest chain of delegations to the sender grows by the delegations it now
+			// leads on to.
+			let mut depth = 1 + Self::delegation_height(dao_id, &member_address, T::MaxDelegationDepth::get());
+			ensure!(depth <= T::MaxDelegationDepth::get(), Error::<T>::DelegationTooDeep);
+			let mut next = Self::delegation(dao_id, &delegate);
+			while let Some(account) = next {
+				ensure!(account != member_address, Error::<T>::DelegationCycle);
+				depth += 1;
+				ensure!(depth <= T::MaxDelegationDepth::get(), Error::<T>::DelegationTooDeep);
+				next = Self::delegation(dao_id, &account);
+			}
+
+			let mut delegators = Self::delegators(dao_id, &delegate);
+			delegators.retain(|d| d != &member_address);
+			ensure!((delegators.len() as u32) < T::MaxDelegators::get(), Error::<T>::TooManyDelegators);
+
+			Self::remove_delegation(dao_id, &member_address);
+			delegators.push(member_address.clone());
+			<Delegators<T>>::insert(dao_id, &delegate, delegators);
+			<Delegations<T>>::insert(dao_id, &member_address, &delegate);
+
+			Self::deposit_event(RawEvent::VotesDelegated(dao_id, member_address, delegate));
+			Ok(())
+		}
+
+		/// Take back the sender's voting weight from their delegate.
+		///
+		/// Ballots cast before keep the weight they were cast with, the sender can still override
+		/// them by voting.
+		///
+		/// Must be called with the delegate key of a member.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(4, 2)]
+		pub fn undelegate_votes(origin, dao_id: DaoId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let member_address = Self::member_by_delegate_key(dao_id, &who).ok_or(Error::<T>::NotDelegate)?;
+			ensure!(Self::remove_delegation(dao_id, &member_address), Error::<T>::NotDelegating);
+
+			Self::deposit_event(RawEvent::DelegationRevoked(dao_id, member_address));
+			Ok(())
+		}
+
+		/// Commit to a secret vote on the proposal at `proposal_index` during its voting period.
+		///
+		/// `commitment` is the hash of the vote and a salt, `(Vote, [u8; 32])`. The first
+		/// commitment reserves the DAO's reveal deposit from the sender, later ones replace it.
+		/// Unlike open votes, commitments pay fees.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(7, 2)]
+		pub fn commit_vote(origin, dao_id: DaoId, proposal_index: ProposalIndex, commitment: T::Hash) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::params(dao_id)?;
+			let (member_address, _) = Self::ensure_delegate(dao_id, &who)?;
+			let proposal_id = Self::proposal_queue(dao_id, proposal_index).ok_or(Error::<T>::UnknownProposalIndex)?;
+			let proposal = Self::proposals(dao_id, proposal_id).ok_or(Error::<T>::UnknownProposal)?;
+			let params = Self::params_of(dao_id, proposal_id).map_err(|_| Error::<T>::VotingPeriodExpired)?;
+			ensure!(params.reveal_period_length > 0, Error::<T>::NotSecretBallot);
+
+			let current_period = Self::current_period(dao_id);
+			ensure!(current_period >= proposal.starting_period, Error::<T>::VotingNotStarted);
+			let voting_ends = proposal.starting_period + params.voting_period_length;
+			ensure!(current_period < voting_ends, Error::<T>::VotingPeriodExpired);
+
+			let sealed = match Self::sealed_vote((dao_id, proposal_id), &member_address) {
+				Some(sealed) => SealedVote { hash: commitment, ..sealed },
+				None => {
+					T::Currency::reserve(&who, params.reveal_deposit)?;
+					SealedVote {
+						hash: commitment,
+						depositor: who,
+						deposit: params.reveal_deposit,
+						reveal_ends: voting_ends + params.reveal_period_length,
+					}
+				}
+			};
+			<SealedVotes<T>>::insert((dao_id, proposal_id), &member_address, sealed);
+
+			Self::deposit_event(RawEvent::VoteCommitted(dao_id, proposal_index, member_address));
+			Ok(())
+		}
+
+		/// Reveal the vote the sender committed to on the proposal at `proposal_index` and count
+		/// it, returning the reveal deposit.
+		///
+		/// Only possible in the reveal period following voting. The vote is share-weighted and
+		/// counts as of now, like an open vote cast at the time of the reveal.
+		#[weight = Module::<T>::vote_weight(Module::<T>::max_delegators_visited())
+			+ T::DbWeight::get().reads_writes(3, 2)]
+		pub fn reveal_vote(
+			origin,
+			dao_id: DaoId,
+			proposal_index: ProposalIndex,
+			vote: Vote,
+			salt: [u8; 32],
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			Self::params(dao_id)?;
+			let (member_address, member) = Self::ensure_delegate(dao_id, &who)?;
+			let proposal_id = Self::proposal_queue(dao_id, proposal_index).ok_or(Error::<T>::UnknownProposalIndex)?;
+			let proposal = Self::proposals(dao_id, proposal_id).ok_or(Error::<T>::UnknownProposal)?;
+			let params = Self::params_of(dao_id, proposal_id).map_err(|_| Error::<T>::NotRevealPeriod)?;
+			ensure!(params.reveal_period_length > 0, Error::<T>::NotSecretBallot);
+
+			let sealed = Self::sealed_vote((dao_id, proposal_id), &member_address).ok_or(Error::<T>::NoCommitment)?;
+			let current_period = Self::current_period(dao_id);
+			ensure!(
+				current_period >= proposal.starting_period + params.voting_period_length
+					&& current_period < sealed.reveal_ends,
+				Error::<T>::NotRevealPeriod
+			);
+			ensure!(T::Hashing::hash_of(&(vote, salt)) == sealed.hash, Error::<T>::InvalidReveal);
+
+			<SealedVotes<T>>::remove((dao_id, proposal_id), &member_address);
+			T::Currency::unreserve(&sealed.depositor, sealed.deposit);
+			let visited = Self::cast_vote(
+				dao_id, proposal_index, proposal_id, proposal, member_address, member, vote, Conviction::None,
+			)?;
+			Ok(Some(Self::vote_weight(visited) + T::DbWeight::get().reads_writes(3, 2)).into())
+		}
+
+		/// Move the reveal deposit of a vote `member` committed to on the proposal at
+		/// `proposal_index` but did not reveal to the guild bank. Anyone may call this once the
+		/// reveal period is over.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(5, 4)]
+		pub fn punish_unrevealed(
+			origin,
+			dao_id: DaoId,
+			proposal_index: ProposalIndex,
+			member: T::AccountId,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			let proposal_id = Self::proposal_queue(dao_id, proposal_index).ok_or(Error::<T>::UnknownProposalIndex)?;
+			let sealed = Self::sealed_vote((dao_id, proposal_id), &member).ok_or(Error::<T>::NoCommitment)?;
+			ensure!(Self::current_period(dao_id) >= sealed.reveal_ends, Error::<T>::RevealNotOver);
+
+			<SealedVotes<T>>::remove((dao_id, proposal_id), &member);
+			let left = T::Currency::repatriate_reserved(
+				&sealed.depositor,
+				&Self::account_id(dao_id),
+				sealed.deposit,
+				BalanceStatus::Free,
+			).unwrap_or(sealed.deposit);
+			let penalty = sealed.deposit.saturating_sub(left);
+			<GuildBalance<T>>::mutate(dao_id, |b| *b = b.saturating_add(penalty));
+
+			Self::deposit_event(RawEvent::UnrevealedVotePunished(dao_id, proposal_index, member, penalty));
+			Ok(())
+		}
+
+		/// Pay or dispatch the action of the passed proposal `proposal_id` once its timelock has
+		/// passed. Anyone may call this.
+		///
+		/// The payment stays in the guild bank until then, so members who ragequit during the
+		/// timelock take their fair share of it. Fails while the guild bank cannot cover it.
+		/// Weighs up to `MaxActionWeight` more to dispatch an action, the unused part is refunded.
+		#[weight = Module::<T>::execute_timelocked_weight(T::MaxActionWeight::get())]
+		pub fn execute_timelocked(origin, dao_id: DaoId, proposal_id: ProposalId) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+			Self::ensure_not_paused(dao_id)?;
+			let ready_at = Self::timelock(dao_id, proposal_id).ok_or(Error::<T>::NotTimelocked)?;
+			ensure!(Self::current_period(dao_id) >= ready_at, Error::<T>::TimelockNotOver);
+
+			let mut action_weight = 0;
+			if let Some(action) = <ProposalActions<T>>::take(dao_id, proposal_id) {
+				action_weight = Self::execute(dao_id, proposal_id, action);
+			} else {
+				let proposal = Self::proposals(dao_id, proposal_id).ok_or(Error::<T>::UnknownProposal)?;
+				ensure!(
+					proposal.payment_requested <= Self::guild_balance(dao_id),
+					Error::<T>::InsufficientBalance
+				);
+				ensure!(
+					!<ProposalVesting<T>>::contains_key(dao_id, proposal_id)
+						|| Self::has_room_for_grant(dao_id, &proposal.applicant),
+					Error::<T>::TooManyGrants
+				);
+				Self::pay(dao_id, proposal_id, &proposal);
+			}
+			Timelocks::remove(dao_id, proposal_id);
+			<CancelApprovals<T>>::remove(dao_id, proposal_id);
+
+			Self::deposit_event(RawEvent::TimelockExecuted(dao_id, proposal_id));
+			Ok(Some(Self::execute_timelocked_weight(action_weight)).into())
+		}
+
+		/// Approve cancelling the payment or action of `proposal_id` while it waits in the
+		/// timelock. It is cancelled once two thirds of the DAO's guardians approve.
+		///
+		/// Must be called by a guardian. Approvals of accounts that are no longer guardians do not
+		/// count.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(4, 3)]
+		pub fn cancel_timelocked(origin, dao_id: DaoId, proposal_id: ProposalId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let guardians = Self::guardians(dao_id);
+			ensure!(guardians.binary_search(&who).is_ok(), Error::<T>::NotGuardian);
+			let ready_at = Self::timelock(dao_id, proposal_id).ok_or(Error::<T>::NotTimelocked)?;
+			ensure!(Self::current_period(dao_id) < ready_at, Error::<T>::TimelockOver);
+			let mut approvals = Self::cancel_approvals(dao_id, proposal_id);
+			ensure!(!approvals.contains(&who), Error::<T>::AlreadyApproved);
+
+			approvals.retain(|guardian| guardians.binary_search(guardian).is_ok());
+			approvals.push(who.clone());
+			if 3 * approvals.len() >= 2 * guardians.len() {
+				Timelocks::remove(dao_id, proposal_id);
+				<ProposalActions<T>>::remove(dao_id, proposal_id);
+				<ProposalVesting<T>>::remove(dao_id, proposal_id);
+				<ProposalMilestones<T>>::remove(dao_id, proposal_id);
+				<CancelApprovals<T>>::remove(dao_id, proposal_id);
+				Self::deposit_event(RawEvent::TimelockCancelled(dao_id, proposal_id));
+			} else {
+				<CancelApprovals<T>>::insert(dao_id, proposal_id, approvals);
+				Self::deposit_event(RawEvent::CancellationApproved(dao_id, proposal_id, who));
+			}
+			Ok(())
+		}
+
+		/// Replace the guardians of the DAO, which must be the origin.
+		///
+		/// DAOs set their guardians through action proposals. Duplicates are ignored.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(1, 1)]
+		pub fn set_guardians(origin, guardians: Vec<T::AccountId>) -> DispatchResult {
+			let dao_id = T::DaoOrigin::ensure_origin(origin)?;
+			Self::params(dao_id)?;
+			let mut guardians = guardians;
+			guardians.sort();
+			guardians.dedup();
+			ensure!(guardians.len() <= T::MaxGuardians::get() as usize, Error::<T>::TooManyGuardians);
+
+			<Guardians<T>>::insert(dao_id, guardians);
+			Self::deposit_event(RawEvent::GuardiansSet(dao_id));
+			Ok(())
+		}
+
+		/// Approve vetoing the proposal at `proposal_index` during its grace period, once its
+		/// votes are final and if it would pass. It is vetoed once two thirds of the DAO's
+		/// guardians approve.
+		///
+		/// A veto does not decide the proposal. Members vote on ratifying it for a voting period,
+		/// with the shares that count on the proposal, and processing waits for that vote. If
+		/// more shares ratify than overturn the veto, the proposal fails. Must be called by a
+		/// guardian.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(17, 3)]
+		pub fn veto_proposal(origin, dao_id: DaoId, proposal_index: ProposalIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let guardians = Self::guardians(dao_id);
+			ensure!(guardians.binary_search(&who).is_ok(), Error::<T>::NotGuardian);
+			let proposal_id = Self::proposal_queue(dao_id, proposal_index).ok_or(Error::<T>::UnknownProposalIndex)?;
+			let proposal = Self::proposals(dao_id, proposal_id).ok_or(Error::<T>::UnknownProposal)?;
+			let params = Self::params_of(dao_id, proposal_id).map_err(|_| Error::<T>::NotGracePeriod)?;
+			ensure!(!Vetoes::contains_key(dao_id, proposal_id), Error::<T>::AlreadyVetoed);
+
+			let current_period = Self::current_period(dao_id);
+			let grace_starts = proposal.starting_period + params.voting_period_length + params.reveal_period_length;
+			ensure!(
+				current_period >= grace_starts && current_period < grace_starts + params.grace_period_length,
+				Error::<T>::NotGracePeriod
+			);
+			let kind = Self::kind_of(dao_id, proposal_id, &proposal);
+			ensure!(Self::did_pass(dao_id, proposal_id, &params, kind, &proposal), Error::<T>::NotPassing);
+			let mut approvals = Self::veto_approvals(dao_id, proposal_id);
+			ensure!(!approvals.contains(&who), Error::<T>::AlreadyApproved);
+
+			approvals.retain(|guardian| guardians.binary_search(guardian).is_ok());
+			approvals.push(who.clone());
+			if 3 * approvals.len() >= 2 * guardians.len() {
+				let ratification_ends = current_period + params.voting_period_length;
+				Vetoes::insert(dao_id, proposal_id, Veto { ratification_ends, ..Default::default() });
+				<VetoApprovals<T>>::remove(dao_id, proposal_id);
+				Self::deposit_event(RawEvent::Vetoed(dao_id, proposal_index, ratification_ends));
+			} else {
+				<VetoApprovals<T>>::insert(dao_id, proposal_id, approvals);
+				Self::deposit_event(RawEvent::VetoApproved(dao_id, proposal_index, who));
+			}
+			Ok(())
+		}
+
+		/// Vote on ratifying the veto of the proposal at `proposal_index`, with the shares of the
+		/// member that count on the proposal. Each member votes once.
+		///
+		/// Must be called with the delegate key of a member holding shares.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(8, 2)]
+		pub fn vote_on_veto(origin, dao_id: DaoId, proposal_index: ProposalIndex, ratify: bool) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let (member_address, member) = Self::ensure_delegate(dao_id, &who)?;
+			let proposal_id = Self::proposal_queue(dao_id, proposal_index).ok_or(Error::<T>::UnknownProposalIndex)?;
+			let mut veto = Self::veto(dao_id, proposal_id).ok_or(Error::<T>::NotVetoed)?;
+			ensure!(Self::current_period(dao_id) < veto.ratification_ends, Error::<T>::RatificationOver);
+			ensure!(
+				!<RatificationVotes<T>>::contains_key((dao_id, proposal_id), &member_address),
+				Error::<T>::AlreadyVoted
+			);
+
+			let shares = Self::voting_shares(dao_id, proposal_id, &member_address, &member);
+			if ratify {
+				veto.ratify = veto.ratify.saturating_add(shares);
+			} else {
+				veto.overturn = veto.overturn.saturating_add(shares);
+			}
+			Vetoes::insert(dao_id, proposal_id, veto);
+			<RatificationVotes<T>>::insert((dao_id, proposal_id), &member_address, ratify);
+
+			Self::deposit_event(RawEvent::RatificationVoteSubmitted(dao_id, proposal_index, member_address, ratify));
+			Ok(())
+		}
+
+		/// Pause the DAO in an emergency. Any guardian may do this.
+		///
+		/// A paused DAO accepts no new proposals or sponsorships and neither processes proposals
+		/// nor executes timelocked payments and actions. Ragequit, withdrawals and votes stay open,
+		/// and yes voters may leave once the proposal they voted on would be due for processing.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(3, 4)]
+		pub fn pause(origin, dao_id: DaoId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::params(dao_id)?;
+			ensure!(Self::guardians(dao_id).binary_search(&who).is_ok(), Error::<T>::NotGuardian);
+			ensure!(!Self::paused(dao_id), Error::<T>::AlreadyPaused);
+
+			Self::set_paused(dao_id, true);
+			Ok(())
+		}
+
+		/// Approve unpausing the DAO, which happens once two thirds of its guardians approve.
+		/// Must be called by a guardian.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(3, 4)]
+		pub fn unpause(origin, dao_id: DaoId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let guardians = Self::guardians(dao_id);
+			ensure!(guardians.binary_search(&who).is_ok(), Error::<T>::NotGuardian);
+			ensure!(Self::paused(dao_id), Error::<T>::NotPaused);
+			let mut approvals = Self::unpause_approvals(dao_id);
+			ensure!(!approvals.contains(&who), Error::<T>::AlreadyApproved);
+
+			approvals.retain(|guardian| guardians.binary_search(guardian).is_ok());
+			approvals.push(who.clone());
+			if 3 * approvals.len() >= 2 * guardians.len() {
+				Self::set_paused(dao_id, false);
+			} else {
+				<UnpauseApprovals<T>>::insert(dao_id, approvals);
+				Self::deposit_event(RawEvent::UnpauseApproved(dao_id, who));
+			}
+			Ok(())
+		}
+
+		/// Support the fast-track vote to pause the DAO, or to unpause it while it is paused,
+		/// with the member's shares. The DAO is paused or unpaused as soon as more than half of
+		/// all shares support it, which starts a new vote.
+		///
+		/// As for proposals, shares count as held at the end of the block before the vote started,
+		/// as far as the member still holds them, against the total shares when it started.
+		/// Support is lowered as members ragequit or are kicked, and a vote that has not passed
+		/// within a voting period ends, so that the next call starts a new one.
+		///
+		/// Must be called with the delegate key of a member holding shares.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(9, 9)]
+		pub fn vote_pause_toggle(origin, dao_id: DaoId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let params = Self::params(dao_id)?;
+			let (member_address, member) = Self::ensure_delegate(dao_id, &who)?;
+			if let Some((block, _)) = Self::pause_snapshot(dao_id) {
+				if block.saturating_add(Self::voting_blocks(&params)) < <system::Module<T>>::block_number() {
+					Self::end_pause_vote(dao_id);
+				}
+			}
+			ensure!(!<PauseSupport<T>>::contains_key(dao_id, &member_address), Error::<T>::AlreadyVoted);
+
+			let (block, total_shares) = Self::pause_snapshot(dao_id).unwrap_or_else(|| (
+				<system::Module<T>>::block_number().saturating_sub(One::one()),
+				Self::total_shares(dao_id),
+			));
+			let shares = member.shares.min(Self::shares_at(dao_id, &member_address, block));
+			let support = Self::pause_support_tally(dao_id).saturating_add(shares);
+			if support.saturating_mul(2) > total_shares {
+				Self::set_paused(dao_id, !Self::paused(dao_id));
+			} else {
+				<PauseSupport<T>>::insert(dao_id, &member_address, shares);
+				PauseSupportTally::insert(dao_id, support);
+				<PauseSnapshot<T>>::insert(dao_id, (block, total_shares));
+				Self::deposit_event(RawEvent::PauseToggleSupported(dao_id, member_address));
+			}
+			Ok(())
+		}
+
+		/// Credit the part of the sender's grant from `proposal_id` that has vested since they
+		/// last claimed to their internal balance, to withdraw with `withdraw_balance`.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(3, 3)]
+		pub fn claim_vested(origin, dao_id: DaoId, proposal_id: ProposalId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut grants = Self::grants(dao_id, &who);
+			let position = grants.iter().position(|(id, _)| *id == proposal_id)
+				.ok_or(Error::<T>::UnknownGrant)?;
+			let grant = &mut grants[position].1;
+			let amount = Self::vested(grant).saturating_sub(grant.claimed);
+			ensure!(!amount.is_zero(), Error::<T>::NothingVested);
+
+			grant.claimed = grant.claimed.saturating_add(amount);
+			if grant.claimed >= grant.total {
+				grants.remove(position);
+			}
+			if grants.is_empty() {
+				<Grants<T>>::remove(dao_id, &who);
+			} else {
+				<Grants<T>>::insert(dao_id, &who, grants);
+			}
+			Self::internal_transfer(dao_id, Account::Vesting, Account::User(who.clone()), amount);
+
+			Self::deposit_event(RawEvent::VestedClaimed(dao_id, proposal_id, who, amount));
+			Ok(())
+		}
+
+		/// Revoke the grant `recipient` was paid from `proposal_id`, crediting them what has
+		/// vested and returning the rest to the guild balance. The DAO, which must be the origin,
+		/// does this through an action proposal.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 4)]
+		pub fn revoke_grant(origin, recipient: T::AccountId, proposal_id: ProposalId) -> DispatchResult {
+			let dao_id = T::DaoOrigin::ensure_origin(origin)?;
+			let mut grants = Self::grants(dao_id, &recipient);
+			let position = grants.iter().position(|(id, _)| *id == proposal_id)
+				.ok_or(Error::<T>::UnknownGrant)?;
+
+			let (_, grant) = grants.remove(position);
+			if grants.is_empty() {
+				<Grants<T>>::remove(dao_id, &recipient);
+			} else {
+				<Grants<T>>::insert(dao_id, &recipient, grants);
+			}
+			Self::end_grant(dao_id, &recipient, proposal_id, grant);
+			Ok(())
+		}
+
+		/// Support approving the next milestone of the grant made by `proposal_id` with the
+		/// member's shares. Its tranche is paid as soon as more than half of all shares support
+		/// it, which starts the vote on the milestone after it.
+		///
+		/// As for proposals, support counts the shares the member held at the end of the block
+		/// before the vote on the milestone started, as far as they still hold them, against the
+		/// total shares when it started. It is lowered as they ragequit shares or are kicked, and
+		/// a vote that has not passed within a voting period ends, so that the next call starts a
+		/// new one. A member may support up to `MaxMilestoneApprovals` grants at once. If the
+		/// recipient has been kicked, the grant is revoked instead of paying the tranche.
+		///
+		/// Must be called with the delegate key of a member holding shares.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(11 + T::MaxMilestoneApprovals::get() as Weight, 8)]
+		pub fn approve_milestone(origin, dao_id: DaoId, proposal_id: ProposalId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let params = Self::params(dao_id)?;
+			Self::ensure_not_paused(dao_id)?;
+			let mut grant = Self::milestone_grant(dao_id, proposal_id).ok_or(Error::<T>::UnknownMilestoneGrant)?;
+			let (member_address, member) = Self::ensure_delegate(dao_id, &who)?;
+			let key = (dao_id, proposal_id);
+			let now = <system::Module<T>>::block_number();
+			let snapshot = Self::milestone_snapshot(dao_id, proposal_id);
+			let expired = snapshot.map_or(false, |(block, _)| block.saturating_add(Self::voting_blocks(&params)) < now);
+			ensure!(
+				expired || !<MilestoneSupport<T>>::contains_key(&key, &member_address),
+				Error::<T>::AlreadyVoted
+			);
+			let mut supported = Self::milestones_supported(dao_id, &member_address);
+			supported.retain(|id| {
+				*id != proposal_id && <MilestoneSupport<T>>::contains_key((dao_id, *id), &member_address)
+			});
+			ensure!(
+				(supported.len() as u32) < T::MaxMilestoneApprovals::get(),
+				Error::<T>::TooManyMilestoneApprovals
+			);
+
+			let (block, total_shares) = match snapshot {
+				Some(snapshot) if !expired => snapshot,
+				_ => {
+					<MilestoneSupport<T>>::remove_prefix(&key);
+					grant.support = 0;
+					(now.saturating_sub(One::one()), Self::total_shares(dao_id))
+				}
+			};
+			let shares = member.shares.min(Self::shares_at(dao_id, &member_address, block));
+			grant.support = grant.support.saturating_add(shares);
+			if grant.support.saturating_mul(2) > total_shares {
+				Self::pay_tranche(dao_id, proposal_id, grant);
+			} else {
+				<MilestoneSupport<T>>::insert(&key, &member_address, shares);
+				<MilestoneSnapshot<T>>::insert(dao_id, proposal_id, (block, total_shares));
+				<MilestoneGrants<T>>::insert(dao_id, proposal_id, grant);
+				supported.push(proposal_id);
+				Self::deposit_event(RawEvent::MilestoneApprovalSupported(dao_id, proposal_id, member_address.clone()));
+			}
+			Self::set_milestones_supported(dao_id, &member_address, supported);
+			Ok(())
+		}
+
+		/// Withdraw the member's support for approving the next milestone of the grant made by
+		/// `proposal_id`.
+		///
+		/// Must be called with the delegate key of a member holding shares.
+		#[weight = 50_000 + T::DbWeight::get().reads_writes(5, 3)]
+		pub fn revoke_milestone_approval(origin, dao_id: DaoId, proposal_id: ProposalId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let (member_address, _) = Self::ensure_delegate(dao_id, &who)?;
+			let key = (dao_id, proposal_id);
+			ensure!(<MilestoneSupport<T>>::contains_key(&key, &member_address), Error::<T>::NoMilestoneSupport);
+
+			let shares = <MilestoneSupport<T>>::take(&key, &member_address);
+			<MilestoneGrants<T>>::mutate(dao_id, proposal_id, |grant| if let Some(grant) = grant {
+				grant.support = grant.support.saturating_sub(shares);
+			});
+			let mut supported = Self::milestones_supported(dao_id, &member_address);
+			supported.retain(|id| *id != proposal_id);
+			Self::set_milestones_supported(dao_id, &member_address, supported);
+
+			Self::deposit_event(RawEvent::MilestoneApprovalRevoked(dao_id, proposal_id, member_address));
+			Ok(())
+		}
+
+		/// Cancel the milestone grant made by `proposal_id`, returning its unpaid tranches to the
+		/// guild balance. The DAO, which must be the origin, does this through an action proposal.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(3, 5)]
+		pub fn cancel_milestone_grant(origin, proposal_id: ProposalId) -> DispatchResult {
+			let dao_id = T::DaoOrigin::ensure_origin(origin)?;
+			let grant = <MilestoneGrants<T>>::take(dao_id, proposal_id).ok_or(Error::<T>::UnknownMilestoneGrant)?;
+
+			let unpaid = Self::unpaid_tranches(&grant);
+			Self::internal_transfer(dao_id, Account::Milestones, Account::Guild, unpaid);
+			<MilestoneSupport<T>>::remove_prefix((dao_id, proposal_id));
+			<MilestoneSnapshot<T>>::remove(dao_id, proposal_id);
+
+			Self::deposit_event(RawEvent::MilestoneGrantCancelled(dao_id, proposal_id, unpaid));
+			Ok(())
+		}
+	}
+}
+
+/// Holders of internal balances in the guild bank.
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+enum Account<AccountId> {
+	Guild,
+	Escrow,
+	Vesting,
+	Milestones,
+	User(AccountId),
+}
+
+impl<T: Trait> Module<T> {
+	/// The account holding the funds of the guild bank of `dao_id`.
+	pub fn account_id(dao_id: DaoId) -> T::AccountId {
+		T::ModuleId::get().into_sub_account(dao_id)
+	}
+
+	/// The period `dao_id` is currently in.
+	pub fn current_period(dao_id: DaoId) -> u64 {
+		let period_duration = match Self::dao(dao_id) {
+			Some(params) if !params.period_duration.is_zero() => params.period_duration,
+			_ => return 0,
+		};
+		let elapsed = <system::Module<T>>::block_number().saturating_sub(Self::summoning_time(dao_id));
+		(elapsed / period_duration).saturated_into::<u64>()
+	}
+
+	/// Total shares and loot across all members.
+	pub fn total_shares_and_loot(dao_id: DaoId) -> Shares {
+		Self::total_shares(dao_id).saturating_add(Self::total_loot(dao_id))
+	}
+
+	/// Whether `who` is a member that has been kicked from the guild.
+	pub fn is_jailed(dao_id: DaoId, who: &T::AccountId) -> bool {
+		Self::members(dao_id, who).map_or(false, |m| m.jailed)
+	}
+
+	/// Whether `member` may ragequit, i.e. every proposal they voted yes on is processed, or the
+	/// DAO is paused and their latest one would be due for processing but for the pause.
+	pub fn can_ragequit(dao_id: DaoId, member: &Member<T::AccountId>) -> bool {
+		member.highest_index_yes_vote
+			.and_then(|index| Self::proposal_queue(dao_id, index))
+			.and_then(|id| Self::proposals(dao_id, id).map(|p| (id, p)))
+			.map_or(true, |(id, p)| p.processed || (Self::paused(dao_id) && Self::params_of(dao_id, id)
+				.map_or(true, |params| Self::current_period(dao_id) >= p.starting_period
+					+ params.voting_period_length
+					+ params.reveal_period_length
+					+ params.grace_period_length)))
+	}
+
+	/// Check the invariants the DAOs' storage must satisfy between blocks.
+	///
+	/// Runs at the end of every block in test and debug builds with the `try-runtime` feature,
+	/// and is exposed through `DaoApi::try_state` so that operators can check any block. Reads
+	/// every member, queued proposal and internal balance of every DAO, so it must not be called
+	/// from on-chain code paths in production.
+	pub fn do_try_state() -> Result<(), &'static str> {
+		for dao_id in 0..Self::dao_count() {
+			Self::do_try_state_of(dao_id)?;
+		}
+
+		for (dao_id, proposal_id, _) in <ProposalActions<T>>::iter() {
+			let proposal = Self::proposals(dao_id, proposal_id).ok_or("action of a proposal that does not exist")?;
+			ensure!(
+				(!proposal.processed && !proposal.cancelled) || Self::timelock(dao_id, proposal_id).is_some(),
+				"action outlived its proposal"
+			);
+		}
+		for (dao_id, proposal_id, _) in Vetoes::iter() {
+			let proposal = Self::proposals(dao_id, proposal_id).ok_or("veto of a proposal that does not exist")?;
+			ensure!(!proposal.processed, "veto outlived its proposal");
+		}
+		for ((dao_id, proposal_id), _, _) in <RatificationVotes<T>>::iter() {
+			let proposal = Self::proposals(dao_id, proposal_id)
+				.ok_or("ratification vote on a proposal that does not exist")?;
+			ensure!(!proposal.processed, "ratification vote outlived its proposal");
+			ensure!(Vetoes::contains_key(dao_id, proposal_id), "ratification vote without a veto");
+		}
+		for (dao_id, proposal_id, _) in Timelocks::iter() {
+			let proposal = Self::proposals(dao_id, proposal_id).ok_or("timelock of a proposal that does not exist")?;
+			ensure!(proposal.processed && proposal.did_pass, "timelock of a proposal that did not pass");
+		}
+		for (dao_id, proposal_id, _) in <ProposalVesting<T>>::iter() {
+			let proposal = Self::proposals(dao_id, proposal_id)
+				.ok_or("vesting schedule of a proposal that does not exist")?;
+			ensure!(
+				(!proposal.processed && !proposal.cancelled) || Self::timelock(dao_id, proposal_id).is_some(),
+				"vesting schedule outlived its proposal"
+			);
+		}
+
+		for (dao_id, proposal_id, _) in <ProposalMilestones<T>>::iter() {
+			let proposal = Self::proposals(dao_id, proposal_id).ok_or("milestones of a proposal that does not exist")?;
+			ensure!(
+				(!proposal.processed && !proposal.cancelled) || Self::timelock(dao_id, proposal_id).is_some(),
+				"milestones outlived their proposal"
+			);
+		}
+
+		let mut unpaid = vec![BalanceOf::<T>::zero(); Self::dao_count() as usize];
+		for (dao_id, proposal_id, grant) in <MilestoneGrants<T>>::iter() {
+			ensure!((grant.next as usize) < grant.milestones.len(), "paid milestone grant was kept");
+			let support = <MilestoneSupport<T>>::iter_prefix((dao_id, proposal_id))
+				.fold(0 as Shares, |total, (_, shares)| total.saturating_add(shares));
+			ensure!(support == grant.support, "milestone support differs from the sum of support");
+			ensure!(
+				support.is_zero() || Self::milestone_snapshot(dao_id, proposal_id).is_some(),
+				"milestone vote has support but no snapshot"
+			);
+			for (who, _) in <MilestoneSupport<T>>::iter_prefix((dao_id, proposal_id)) {
+				ensure!(
+					Self::milestones_supported(dao_id, &who).contains(&proposal_id),
+					"milestone support is missing from the member's supported grants"
+				);
+			}
+			let total = unpaid.get_mut(dao_id as usize).ok_or("milestone grant of a DAO that does not exist")?;
+			*total = total.saturating_add(Self::unpaid_tranches(&grant));
+		}
+		for (dao_id, total) in unpaid.into_iter().enumerate() {
+			ensure!(
+				total == Self::milestone_balance(dao_id as DaoId),
+				"MilestoneBalance differs from unpaid tranches"
+			);
+		}
+
+		let mut unclaimed = vec![BalanceOf::<T>::zero(); Self::dao_count() as usize];
+		for (dao_id, _, grants) in <Grants<T>>::iter() {
+			ensure!(!grants.is_empty(), "recipient without grants was kept");
+			ensure!(grants.len() as u32 <= T::MaxGrants::get(), "recipient has too many grants");
+			let total = unclaimed.get_mut(dao_id as usize).ok_or("grant of a DAO that does not exist")?;
+			for (_, grant) in grants {
+				ensure!(grant.claimed < grant.total, "fully claimed grant was kept");
+				*total = total.saturating_add(grant.total - grant.claimed);
+			}
+		}
+		for (dao_id, total) in unclaimed.into_iter().enumerate() {
+			ensure!(total == Self::vesting_balance(dao_id as DaoId), "VestingBalance differs from unclaimed grants");
+		}
+
+		for (dao_id, proposal_id, _) in <ParamChanges<T>>::iter() {
+			let proposal = Self::proposals(dao_id, proposal_id)
+				.ok_or("parameter change of a proposal that does not exist")?;
+			ensure!(!proposal.processed && !proposal.cancelled, "parameter change outlived its proposal");
+		}
+
+		for (dao_id, proposal_id, _) in <ProposalParams<T>>::iter() {
+			let proposal = Self::proposals(dao_id, proposal_id)
+				.ok_or("parameters of a proposal that does not exist")?;
+			ensure!(!proposal.processed && !proposal.cancelled, "parameters outlived their proposal");
+		}
+
+		Ok(())
+	}
+
+	/// Check the invariants of a single DAO.
+	fn do_try_state_of(dao_id: DaoId) -> Result<(), &'static str> {
+		ensure!(Self::dao(dao_id).is_some(), "DAO below the DAO count has no parameters");
+
+		let mut total_shares: Shares = 0;
+		let mut total_loot: Shares = 0;
+		for (who, member) in <Members<T>>::iter_prefix(dao_id) {
+			ensure!(!member.jailed || member.shares == 0, "jailed member holds shares");
+			ensure!(
+				member.shares == 0 || Self::member_by_delegate_key(dao_id, &member.delegate_key) == Some(who),
+				"member's delegate key does not map back to the member"
+			);
+			let checkpoints = Self::share_checkpoints(dao_id, &who);
+			ensure!(checkpoints.len() as u32 <= T::MaxCheckpoints::get(), "member has too many share checkpoints");
+			ensure!(
+				checkpoints.windows(2).all(|pair| pair[0].0 < pair[1].0),
+				"share checkpoints are not in block order"
+			);
+			ensure!(
+				checkpoints.last().map_or(0, |(_, shares)| *shares) == member.shares,
+				"latest share checkpoint differs from the member's shares"
+			);
+			total_shares = total_shares.checked_add(member.shares).ok_or("member shares overflow")?;
+			total_loot = total_loot.checked_add(member.loot).ok_or("member loot overflow")?;
+		}
+		ensure!(total_shares == Self::total_shares(dao_id), "TotalShares differs from the sum of member shares");
+		ensure!(total_loot == Self::total_loot(dao_id), "TotalLoot differs from the sum of member loot");
+		let support = <PauseSupport<T>>::iter_prefix(dao_id)
+			.fold(0 as Shares, |total, (_, shares)| total.saturating_add(shares));
+		ensure!(support == Self::pause_support_tally(dao_id), "PauseSupportTally differs from the sum of support");
+		ensure!(
+			<PauseSupport<T>>::iter_prefix(dao_id).next().is_none() || Self::pause_snapshot(dao_id).is_some(),
+			"fast-track vote has support but no snapshot"
+		);
+
+		let internal_balances = <UserBalances<T>>::iter_prefix(dao_id).fold(
+			Self::guild_balance(dao_id)
+				.saturating_add(Self::escrow_balance(dao_id))
+				.saturating_add(Self::vesting_balance(dao_id))
+				.saturating_add(Self::milestone_balance(dao_id)),
+			|total, (_, balance)| total.saturating_add(balance),
+		);
+		ensure!(
+			T::Currency::free_balance(&Self::account_id(dao_id)) >= internal_balances,
+			"guild bank holds less than the internal balances"
+		);
+
+		let queue_length = Self::proposal_queue_length(dao_id);
+		let mut unprocessed_seen = false;
+		for index in 0..queue_length {
+			let proposal_id = Self::proposal_queue(dao_id, index).ok_or("proposal queue indices are not contiguous")?;
+			let proposal = Self::proposals(dao_id, proposal_id).ok_or("queued proposal does not exist")?;
+			ensure!(proposal.sponsored, "queued proposal is not sponsored");
+			ensure!(
+				proposal.processed || <ProposalParams<T>>::contains_key(dao_id, proposal_id),
+				"unprocessed proposal has no parameters"
+			);
+			ensure!(!(proposal.processed && unprocessed_seen), "proposals were processed out of order");
+			ensure!(
+				proposal.processed == (index < Self::processed_queue_length(dao_id)),
+				"ProcessedQueueLength differs from the processed proposals"
+			);
+			unprocessed_seen |= !proposal.processed;
+		}
+		ensure!(
+			ProposalQueue::iter_prefix(dao_id).count() as u64 == queue_length,
+			"proposal queue has entries past its length"
+		);
+
+		let delegations: Vec<_> = <Delegations<T>>::iter_prefix(dao_id).collect();
+		for (who, delegate) in delegations.iter() {
+			ensure!(Self::members(dao_id, delegate).is_some(), "delegation to an account that is not a member");
+			ensure!(
+				Self::delegators(dao_id, delegate).contains(who),
+				"delegation is missing from the delegate's delegators"
+			);
+			// Following more delegations than there are means going round a cycle.
+			let mut next = Some(delegate.clone());
+			for _ in 0..delegations.len() {
+				next = next.and_then(|account| Self::delegation(dao_id, account));
+			}
+			ensure!(next.is_none(), "delegations form a cycle");
+		}
+		for (who, member) in <Members<T>>::iter_prefix(dao_id) {
+			let commitments = Self::commitments(dao_id, &who);
+			ensure!(
+				commitments.len() as u32 <= T::MaxCommitments::get(),
+				"member committed shares to too many proposals"
+			);
+			ensure!(
+				member.jailed || Self::locked_shares(dao_id, &who) <= member.shares,
+				"member committed more shares than they hold"
+			);
+			ensure!(
+				member.jailed || Self::conviction_locked(dao_id, &who) <= member.shares,
+				"member's conviction locks more shares than they hold"
+			);
+		}
+		for (delegate, delegators) in <Delegators<T>>::iter_prefix(dao_id) {
+			ensure!(delegators.len() as u32 <= T::MaxDelegators::get(), "member has too many delegators");
+			for who in delegators {
+				ensure!(Self::delegation(dao_id, &who) == Some(delegate.clone()), "delegator does not delegate");
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Parameters of the genesis DAO, taken from the pallet's `Trait`.
+	fn genesis_params() -> DaoParams<T::BlockNumber, BalanceOf<T>> {
+		DaoParams {
+			period_duration: T::PeriodDuration::get(),
+			voting_period_length: T::VotingPeriodLength::get(),
+			grace_period_length: T::GracePeriodLength::get(),
+			proposal_deposit: T::ProposalDeposit::get(),
+			processing_reward: T::ProcessingReward::get(),
+			dilution_bound: T::DilutionBound::get(),
+			thresholds: T::Thresholds::get(),
+			voting_mode: VotingMode::default(),
+			reveal_period_length: 0,
+			reveal_deposit: Zero::zero(),
+			timelock_periods: 0,
+		}
+	}
+
+	/// Parameters of `dao_id`, if it exists.
+	fn params(dao_id: DaoId) -> Result<DaoParams<T::BlockNumber, BalanceOf<T>>, Error<T>> {
+		Self::dao(dao_id).ok_or(Error::<T>::UnknownDao)
+	}
+
+	/// Number of blocks in the voting period of `params`.
+	fn voting_blocks(params: &DaoParams<T::BlockNumber, BalanceOf<T>>) -> T::BlockNumber {
+		params.period_duration.saturating_mul(params.voting_period_length.saturated_into())
+	}
+
+	/// Parameters that apply to the unprocessed proposal `proposal_id` of `dao_id`.
+	fn params_of(dao_id: DaoId, proposal_id: ProposalId) -> Result<DaoParams<T::BlockNumber, BalanceOf<T>>, Error<T>> {
+		Self::proposal_params(dao_id, proposal_id).ok_or(Error::<T>::AlreadyProcessed)
+	}
+
+	/// Check that a DAO can run with `params`.
+	fn ensure_valid_params(params: &DaoParams<T::BlockNumber, BalanceOf<T>>) -> DispatchResult {
+		ensure!(
+			!params.period_duration.is_zero()
+				&& params.dilution_bound > 0
+				&& params.processing_reward <= params.proposal_deposit
+				&& (params.reveal_period_length == 0 || params.voting_mode == VotingMode::ShareWeighted),
+			Error::<T>::InvalidParams
+		);
+		Ok(())
+	}
+
+	/// Store a new DAO summoned now and mint the summoners' shares, returning its id.
+	///
+	/// The guild bank must be endowed by the caller.
+	fn create_dao(params: DaoParams<T::BlockNumber, BalanceOf<T>>, summoners: &[(T::AccountId, Shares)]) -> DaoId {
+		let dao_id = Self::dao_count();
+		DaoCount::put(dao_id + 1);
+		<Daos<T>>::insert(dao_id, params);
+		<SummoningTime<T>>::insert(dao_id, <system::Module<T>>::block_number());
+		for (who, shares) in summoners {
+			Self::mint(dao_id, who, *shares, 0);
+		}
+		dao_id
+	}
+
+	/// Resolve `delegate_key` to a member holding shares.
+	fn ensure_delegate(
+		dao_id: DaoId,
+		delegate_key: &T::AccountId,
+	) -> Result<(T::AccountId, Member<T::AccountId>), Error<T>> {
+		let member_address = Self::member_by_delegate_key(dao_id, delegate_key).ok_or(Error::<T>::NotDelegate)?;
+		let member = Self::members(dao_id, &member_address).ok_or(Error::<T>::NotDelegate)?;
+		ensure!(member.shares > 0, Error::<T>::NotDelegate);
+		Ok((member_address, member))
+	}
+
+	/// Weight of `process_proposal` when the proposal's action weighs `action_weight`.
+	///
+	/// A guild kick visits up to `MaxGrants` grants and `MaxMilestoneApprovals` milestone
+	/// approvals of the kicked member.
+	fn process_weight(action_weight: Weight) -> Weight {
+		let visited = T::MaxGrants::get() as Weight + 2 * T::MaxMilestoneApprovals::get() as Weight;
+		100_000 + T::DbWeight::get().reads_writes(27 + visited, 25 + visited) + action_weight
+	}
+
+	/// Weight of `execute_timelocked` when the action weighs `action_weight`.
+	fn execute_timelocked_weight(action_weight: Weight) -> Weight {
+		50_000 + T::DbWeight::get().reads_writes(7 + T::MaxGrants::get() as Weight, 11) + action_weight
+	}
+
+	/// Check that `dao_id` is not paused.
+	fn ensure_not_paused(dao_id: DaoId) -> Result<(), Error<T>> {
+		ensure!(!Self::paused(dao_id), Error::<T>::DaoPaused);
+		Ok(())
+	}
+
+	/// Pause or unpause `dao_id`, ending the fast-track vote and the guardians' approvals.
+	fn set_paused(dao_id: DaoId, paused: bool) {
+		if paused {
+			Paused::insert(dao_id, true);
+			Self::deposit_event(RawEvent::Paused(dao_id));
+		} else {
+			Paused::remove(dao_id);
+			Self::deposit_event(RawEvent::Unpaused(dao_id));
+		}
+		Self::end_pause_vote(dao_id);
+		<UnpauseApprovals<T>>::remove(dao_id);
+	}
+
+	/// End the fast-track vote of `dao_id`, dropping its support.
+	fn end_pause_vote(dao_id: DaoId) {
+		<PauseSupport<T>>::remove_prefix(dao_id);
+		PauseSupportTally::remove(dao_id);
+		<PauseSnapshot<T>>::remove(dao_id);
+	}
+
+	/// Lower the support of `who` for the fast-track vote to the `shares` they still hold,
+	/// dropping it where they hold none.
+	fn lower_pause_support(dao_id: DaoId, who: &T::AccountId, shares: Shares) {
+		if !<PauseSupport<T>>::contains_key(dao_id, who) {
+			return;
+		}
+		let support = Self::pause_support(dao_id, who);
+		PauseSupportTally::mutate(dao_id, |t| *t = t.saturating_sub(support.saturating_sub(shares)));
+		if shares.is_zero() {
+			<PauseSupport<T>>::remove(dao_id, who);
+		} else {
+			<PauseSupport<T>>::insert(dao_id, who, support.min(shares));
+		}
+	}
+
+	/// Put the payment or action of the passed proposal `proposal_id` in the timelock.
+	fn enter_timelock(dao_id: DaoId, proposal_id: ProposalId, params: &DaoParams<T::BlockNumber, BalanceOf<T>>) {
+		let ready_at = Self::current_period(dao_id).saturating_add(params.timelock_periods);
+		Timelocks::insert(dao_id, proposal_id, ready_at);
+		Self::deposit_event(RawEvent::Timelocked(dao_id, proposal_id, ready_at));
+	}
+
+	/// Pay the applicant of the passed funding proposal `proposal_id` out of the guild balance,
+	/// starting a grant if the payment vests or is paid by milestone.
+	///
+	/// A grant to a member kicked while it waited in the timelock is revoked right away, leaving
+	/// the payment in the guild balance.
+	fn pay(dao_id: DaoId, proposal_id: ProposalId, proposal: &Proposal<T::AccountId, BalanceOf<T>>) {
+		let recipient = proposal.applicant.clone();
+		if <ProposalMilestones<T>>::contains_key(dao_id, proposal_id) {
+			let milestones = <ProposalMilestones<T>>::take(dao_id, proposal_id);
+			if Self::is_jailed(dao_id, &recipient) {
+				Self::deposit_event(RawEvent::GrantRevoked(dao_id, proposal_id, recipient, proposal.payment_requested));
+				return;
+			}
+			Self::internal_transfer(dao_id, Account::Guild, Account::Milestones, proposal.payment_requested);
+			Self::pay_tranche(dao_id, proposal_id, MilestoneGrant { recipient, milestones, next: 0, support: 0 });
+			return;
+		}
+		match <ProposalVesting<T>>::take(dao_id, proposal_id) {
+			Some(_) if Self::is_jailed(dao_id, &recipient) => {
+				Self::deposit_event(RawEvent::GrantRevoked(dao_id, proposal_id, recipient, proposal.payment_requested));
+			}
+			Some(schedule) => {
+				let total = proposal.payment_requested;
+				Self::internal_transfer(dao_id, Account::Guild, Account::Vesting, total);
+				<Grants<T>>::mutate(dao_id, &recipient, |grants| grants.push((proposal_id, Grant {
+					total,
+					claimed: Zero::zero(),
+					start: <system::Module<T>>::block_number(),
+					schedule,
+				})));
+				Self::deposit_event(RawEvent::GrantStarted(dao_id, proposal_id, recipient, total));
+			}
+			None => {
+				Self::internal_transfer(dao_id, Account::Guild, Account::User(recipient), proposal.payment_requested);
+			}
+		}
+	}
+
+	/// Pay the tranche of the next milestone of `grant`, keeping the grant while it has unpaid
+	/// tranches. If the recipient has been kicked from the guild, the grant is revoked instead
+	/// and its unpaid tranches return to the guild balance.
+	fn pay_tranche(
+		dao_id: DaoId,
+		proposal_id: ProposalId,
+		mut grant: MilestoneGrant<T::AccountId, BalanceOf<T>, T::Hash>,
+	) {
+		<MilestoneSupport<T>>::remove_prefix((dao_id, proposal_id));
+		<MilestoneSnapshot<T>>::remove(dao_id, proposal_id);
+		if Self::is_jailed(dao_id, &grant.recipient) {
+			let unpaid = Self::unpaid_tranches(&grant);
+			Self::internal_transfer(dao_id, Account::Milestones, Account::Guild, unpaid);
+			<MilestoneGrants<T>>::remove(dao_id, proposal_id);
+			Self::deposit_event(RawEvent::GrantRevoked(dao_id, proposal_id, grant.recipient, unpaid));
+			return;
+		}
+		let index = grant.next;
+		let amount = grant.milestones.get(index as usize).map_or_else(Zero::zero, |milestone| milestone.amount);
+		Self::internal_transfer(dao_id, Account::Milestones, Account::User(grant.recipient.clone()), amount);
+
+		grant.next += 1;
+		grant.support = 0;
+		if (grant.next as usize) < grant.milestones.len() {
+			<MilestoneGrants<T>>::insert(dao_id, proposal_id, grant);
+		} else {
+			<MilestoneGrants<T>>::remove(dao_id, proposal_id);
+		}
+		Self::deposit_event(RawEvent::TranchePaid(dao_id, proposal_id, index, amount));
+	}
+
+	/// Lower the support of `who` for approving milestones to the `shares` they still hold,
+	/// dropping it where they hold none.
+	fn lower_milestone_support(dao_id: DaoId, who: &T::AccountId, shares: Shares) {
+		let mut supported = Self::milestones_supported(dao_id, who);
+		supported.retain(|&proposal_id| {
+			let key = (dao_id, proposal_id);
+			if !<MilestoneSupport<T>>::contains_key(&key, who) {
+				return false;
+			}
+			let support = Self::milestone_support(&key, who);
+			<MilestoneGrants<T>>::mutate(dao_id, proposal_id, |grant| if let Some(grant) = grant {
+				grant.support = grant.support.saturating_sub(support.saturating_sub(shares));
+			});
+			if shares.is_zero() {
+				<MilestoneSupport<T>>::remove(&key, who);
+				false
+			} else {
+				<MilestoneSupport<T>>::insert(&key, who, support.min(shares));
+				true
+			}
+		});
+		Self::set_milestones_supported(dao_id, who, supported);
+	}
+
+	/// Store the grants `who` supports approving the next milestone of.
+	fn set_milestones_supported(dao_id: DaoId, who: &T::AccountId, supported: Vec<ProposalId>) {
+		if supported.is_empty() {
+			<MilestonesSupported<T>>::remove(dao_id, who);
+		} else {
+			<MilestonesSupported<T>>::insert(dao_id, who, supported);
+		}
+	}
+
+	/// Sum of the tranches of `grant` that are not paid yet.
+	fn unpaid_tranches(grant: &MilestoneGrant<T::AccountId, BalanceOf<T>, T::Hash>) -> BalanceOf<T> {
+		grant.milestones.iter()
+			.skip(grant.next as usize)
+			.fold(Zero::zero(), |total: BalanceOf<T>, milestone| total.saturating_add(milestone.amount))
+	}
+
+	/// Whether `who` may receive another grant from `dao_id`.
+	fn has_room_for_grant(dao_id: DaoId, who: &T::AccountId) -> bool {
+		(Self::grants(dao_id, who).len() as u32) < T::MaxGrants::get()
+	}
+
+	/// Part of `grant` that has vested by the current block.
+	pub fn vested(grant: &Grant<BalanceOf<T>, T::BlockNumber>) -> BalanceOf<T> {
+		let elapsed = <system::Module<T>>::block_number().saturating_sub(grant.start);
+		if elapsed < grant.schedule.cliff {
+			return Zero::zero();
+		}
+		if elapsed >= grant.schedule.duration {
+			return grant.total;
+		}
+		multiply_by_rational(
+			grant.total.saturated_into::<u128>(),
+			elapsed.saturated_into::<u128>(),
+			grant.schedule.duration.saturated_into::<u128>(),
+		)
+			.map(|amount| amount.saturated_into())
+			.unwrap_or_else(|_| Zero::zero())
+	}
+
+	/// End the grants of `who`, crediting them what has vested and returning the rest to the
+	/// guild balance.
+	fn revoke_grants(dao_id: DaoId, who: &T::AccountId) {
+		for (proposal_id, grant) in <Grants<T>>::take(dao_id, who) {
+			Self::end_grant(dao_id, who, proposal_id, grant);
+		}
+	}
+
+	/// Settle the grant `who` was paid from `proposal_id`, already removed from `Grants`.
+	fn end_grant(
+		dao_id: DaoId,
+		who: &T::AccountId,
+		proposal_id: ProposalId,
+		grant: Grant<BalanceOf<T>, T::BlockNumber>,
+	) {
+		let vested = Self::vested(&grant);
+		let unvested = grant.total.saturating_sub(vested);
+		let unclaimed = vested.saturating_sub(grant.claimed);
+		Self::internal_transfer(dao_id, Account::Vesting, Account::User(who.clone()), unclaimed);
+		Self::internal_transfer(dao_id, Account::Vesting, Account::Guild, unvested);
+		Self::deposit_event(RawEvent::GrantRevoked(dao_id, proposal_id, who.clone(), unvested));
+	}
+
+	/// Dispatch the call of a passed action proposal as the DAO, returning the weight it used.
+	fn execute(dao_id: DaoId, proposal_id: ProposalId, action: <T as Trait>::Proposal) -> Weight {
+		let weight = action.get_dispatch_info().weight;
+		let result = action.dispatch(RawOrigin::Dao(dao_id).into());
+		let actual_weight = match &result {
+			Ok(post_info) => post_info.actual_weight,
+			Err(err) => err.post_info.actual_weight,
+		};
+		Self::deposit_event(RawEvent::ActionExecuted(
+			dao_id,
+			proposal_id,
+			result.map(|_| ()).map_err(|e| e.error),
+		));
+		actual_weight.unwrap_or(weight).min(weight)
+	}
+
+	/// Check that the delegate key `who` may cast `vote` on the proposal at `proposal_index` now,
+	/// in voting mode `mode`.
+	fn ensure_can_vote(
+		dao_id: DaoId,
+		who: &T::AccountId,
+		proposal_index: ProposalIndex,
+		vote: Vote,
+		mode: VotingMode,
+	) -> Result<
+		(T::AccountId, Member<T::AccountId>, ProposalId, Proposal<T::AccountId, BalanceOf<T>>),
+		Error<T>,
+	> {
+		Self::params(dao_id)?;
+		let (member_address, member) = Self::ensure_delegate(dao_id, who)?;
+		let proposal_id = Self::proposal_queue(dao_id, proposal_index).ok_or(Error::<T>::UnknownProposalIndex)?;
+		let proposal = Self::proposals(dao_id, proposal_id).ok_or(Error::<T>::UnknownProposal)?;
+		let params = Self::params_of(dao_id, proposal_id).map_err(|_| Error::<T>::VotingPeriodExpired)?;
+		ensure!(params.voting_mode == mode, Error::<T>::WrongVotingMode);
+		ensure!(params.reveal_period_length == 0, Error::<T>::SecretBallot);
+
+		let current_period = Self::current_period(dao_id);
+		ensure!(current_period >= proposal.starting_period, Error::<T>::VotingNotStarted);
+		ensure!(
+			current_period < proposal.starting_period + params.voting_period_length,
+			Error::<T>::VotingPeriodExpired
+		);
+		ensure!(
+			Self::vote_of((dao_id, proposal_id), &member_address).map_or(true, |ballot| ballot.vote != vote),
+			Error::<T>::AlreadyVoted
+		);
+		Ok((member_address, member, proposal_id, proposal))
+	}
+
+	/// Cast or change the share-weighted vote of the delegate key `who`, returning the number of
+	/// delegators visited.
+	fn do_vote(
+		who: &T::AccountId,
+		dao_id: DaoId,
+		proposal_index: ProposalIndex,
+		vote: Vote,
+		conviction: Conviction,
+	) -> Result<(u32, Pays), DispatchError> {
+		let (member_address, member, proposal_id, proposal) =
+			Self::ensure_can_vote(dao_id, who, proposal_index, vote, VotingMode::ShareWeighted)?;
+		let visited = Self::cast_vote(
+			dao_id, proposal_index, proposal_id, proposal, member_address.clone(), member, vote, conviction,
+		)?;
+		Ok((visited, Self::count_vote(dao_id, &member_address)))
+	}
+
+	/// Count the share-weighted `vote` of `member_address`, whose right to cast it was checked,
+	/// returning the number of delegators visited.
+	#[allow(clippy::too_many_arguments)]
+	fn cast_vote(
+		dao_id: DaoId,
+		proposal_index: ProposalIndex,
+		proposal_id: ProposalId,
+		mut proposal: Proposal<T::AccountId, BalanceOf<T>>,
+		member_address: T::AccountId,
+		mut member: Member<T::AccountId>,
+		vote: Vote,
+		conviction: Conviction,
+	) -> Result<u32, DispatchError> {
+		let previous = Self::vote_of((dao_id, proposal_id), &member_address);
+		ensure!(previous.is_none() || conviction == Conviction::None, Error::<T>::ConvictionFixed);
+
+		let mut visited = 0;
+		let ballot = match previous {
+			Some(previous) => {
+				let tally = Self::tally_mut(&mut proposal, previous.vote);
+				*tally = tally.saturating_sub(previous.shares);
+				let bonus = Self::conviction_bonus((dao_id, proposal_id), &member_address);
+				Self::move_conviction(dao_id, proposal_id, bonus, Some(previous.vote), vote);
+				visited = Self::relock_delegators(dao_id, proposal_index, proposal_id, &member_address, vote);
+				Ballot { vote, shares: previous.shares }
+			}
+			None => {
+				Self::withdraw_delegated_vote(dao_id, proposal_index, proposal_id, &mut proposal, &member_address);
+				// Withdrawing the delegated vote may have moved the member's ragequit lock.
+				if let Some(current) = Self::members(dao_id, &member_address) {
+					member = current;
+				}
+				let (delegated, count) = Self::collect_delegated_votes(
+					dao_id, proposal_index, proposal_id, &mut proposal, &member_address, vote,
+				);
+				visited = count;
+				let shares = Self::voting_shares(dao_id, proposal_id, &member_address, &member);
+				if conviction != Conviction::None {
+					let params = Self::params_of(dao_id, proposal_id)?;
+					let bonus = shares.saturating_mul(conviction.multiplier() - 1);
+					<ConvictionBonus<T>>::insert((dao_id, proposal_id), &member_address, bonus);
+					Self::move_conviction(dao_id, proposal_id, bonus, None, vote);
+					let voting_ends = proposal.starting_period.saturating_add(params.voting_period_length);
+					let until = voting_ends
+						.saturating_add(params.voting_period_length.saturating_mul(conviction.lock_periods()));
+					Self::lock_shares(dao_id, &member_address, until, shares);
+				}
+				Ballot { vote, shares: shares.saturating_add(delegated) }
+			}
+		};
+		Self::record_vote(
+			dao_id, proposal_index, proposal_id, proposal, member_address, member, ballot, previous.is_some(),
+		);
+		Ok(visited)
+	}
+
+	/// Move `bonus` from the conviction tally of `from`, if any, to that of `to`.
+	///
+	/// Only yes and no votes have a conviction tally, abstaining does not take sides.
+	fn move_conviction(dao_id: DaoId, proposal_id: ProposalId, bonus: Shares, from: Option<Vote>, to: Vote) {
+		if bonus == 0 {
+			return;
+		}
+		ConvictionTally::mutate(dao_id, proposal_id, |(yes, no)| {
+			match from {
+				Some(Vote::Yes) => *yes = yes.saturating_sub(bonus),
+				Some(Vote::No) => *no = no.saturating_sub(bonus),
+				_ => {}
+			}
+			match to {
+				Vote::Yes => *yes = yes.saturating_add(bonus),
+				Vote::No => *no = no.saturating_add(bonus),
+				Vote::Abstain => {}
+			}
+		});
+	}
+
+	/// Lock `shares` of `member` until the DAO's period `until`, extending any current lock.
+	fn lock_shares(dao_id: DaoId, member: &T::AccountId, until: u64, shares: Shares) {
+		let current_period = Self::current_period(dao_id);
+		<ConvictionLocks<T>>::mutate(dao_id, member, |lock| {
+			let (locked_until, locked) = lock.filter(|(end, _)| *end > current_period).unwrap_or((0, 0));
+			*lock = Some((locked_until.max(until), locked.max(shares)));
+		});
+	}
+
+	/// Shares of `member` that are locked by conviction now.
+	pub fn conviction_locked(dao_id: DaoId, member: &T::AccountId) -> Shares {
+		match Self::conviction_lock(dao_id, member) {
+			Some((until, shares)) if Self::current_period(dao_id) < until => shares,
+			_ => 0,
+		}
+	}
+
+	/// Count `ballot` of `member_address` on the proposal at `proposal_index` and store it.
+	///
+	/// The ballot's shares are added to the tally of its vote, any previous ballot must have been
+	/// taken off already. `changed` tells whether the ballot replaces a previous one.
+	#[allow(clippy::too_many_arguments)]
+	fn record_vote(
+		dao_id: DaoId,
+		proposal_index: ProposalIndex,
+		proposal_id: ProposalId,
+		mut proposal: Proposal<T::AccountId, BalanceOf<T>>,
+		member_address: T::AccountId,
+		mut member: Member<T::AccountId>,
+		ballot: Ballot,
+		changed: bool,
+	) {
+		let vote = ballot.vote;
+		let tally = Self::tally_mut(&mut proposal, vote);
+		*tally = tally.saturating_add(ballot.shares);
+
+		if vote == Vote::Yes {
+			if member.highest_index_yes_vote.map_or(true, |highest| proposal_index > highest) {
+				member.highest_index_yes_vote = Some(proposal_index);
+				<Members<T>>::insert(dao_id, &member_address, member);
+			}
+			proposal.max_total_shares_and_loot_at_yes_vote = proposal
+				.max_total_shares_and_loot_at_yes_vote
+				.max(Self::total_shares_and_loot(dao_id));
+		} else if member.highest_index_yes_vote == Some(proposal_index) {
+			member.highest_index_yes_vote = Self::highest_yes_vote_before(dao_id, &member_address, proposal_index);
+			<Members<T>>::insert(dao_id, &member_address, member);
+		}
+
+		<Proposals<T>>::insert(dao_id, proposal_id, proposal);
+		<VotesByMember<T>>::insert((dao_id, proposal_id), &member_address, ballot);
+
+		if changed {
+			Self::deposit_event(RawEvent::VoteChanged(dao_id, proposal_index, member_address, vote));
+		} else {
+			Self::deposit_event(RawEvent::VoteSubmitted(dao_id, proposal_index, member_address, vote));
+		}
+	}
+
+	/// Shares `member` committed to quadratic votes on proposals that are not processed yet.
+	fn open_commitments(dao_id: DaoId, member: &T::AccountId) -> Vec<(ProposalId, Shares)> {
+		Self::commitments(dao_id, member)
+			.into_iter()
+			.filter(|(proposal_id, _)| Self::proposals(dao_id, proposal_id).map_or(false, |p| !p.processed))
+			.collect()
+	}
+
+	/// Shares of `member` that are locked by quadratic votes on unprocessed proposals.
+	pub fn locked_shares(dao_id: DaoId, member: &T::AccountId) -> Shares {
+		Self::open_commitments(dao_id, member)
+			.iter()
+			.fold(0, |total, (_, committed)| total.saturating_add(*committed))
+	}
+
+	/// The tally of `vote` on `proposal`.
+	fn tally_mut(proposal: &mut Proposal<T::AccountId, BalanceOf<T>>, vote: Vote) -> &mut Shares {
+		match vote {
+			Vote::Yes => &mut proposal.yes_votes,
+			Vote::No => &mut proposal.no_votes,
+			Vote::Abstain => &mut proposal.abstain_votes,
+		}
+	}
+
+	/// Weight of `submit_vote` when it visits `delegators` members delegating to the voter.
+	fn vote_weight(delegators: u32) -> Weight {
+		50_000
+			+ T::DbWeight::get().reads_writes(12, 4)
+			+ T::DbWeight::get().reads_writes(6, 3).saturating_mul(delegators as Weight)
+	}
+
+	/// Maximum number of delegators `submit_vote` may visit, the size of a full tree of
+	/// `MaxDelegators` delegators per member and `MaxDelegationDepth` levels.
+	fn max_delegators_visited() -> u32 {
+		let mut level: u32 = 1;
+		let mut total: u32 = 0;
+		for _ in 0..T::MaxDelegationDepth::get() {
+			level = level.saturating_mul(T::MaxDelegators::get());
+			total = total.saturating_add(level);
+		}
+		total
+	}
+
+	/// Length of the longest chain of delegations ending at `member`, counting at most `limit`.
+	fn delegation_height(dao_id: DaoId, member: &T::AccountId, limit: u32) -> u32 {
+		if limit == 0 {
+			return 0;
+		}
+		Self::delegators(dao_id, member)
+			.iter()
+			.map(|delegator| 1 + Self::delegation_height(dao_id, delegator, limit - 1))
+			.max()
+			.unwrap_or(0)
+	}
+
+	/// Stop delegating the voting weight of `member`, returning whether they delegated it.
+	fn remove_delegation(dao_id: DaoId, member: &T::AccountId) -> bool {
+		match <Delegations<T>>::take(dao_id, member) {
+			Some(delegate) => {
+				<Delegators<T>>::mutate(dao_id, &delegate, |delegators| delegators.retain(|d| d != member));
+				true
+			}
+			None => false,
+		}
+	}
+
+	/// Take the shares of `member` out of the ballot that counted them on behalf of `member`,
+	/// if any, releasing the ragequit lock a yes ballot put on them.
+	fn withdraw_delegated_vote(
+		dao_id: DaoId,
+		proposal_index: ProposalIndex,
+		proposal_id: ProposalId,
+		proposal: &mut Proposal<T::AccountId, BalanceOf<T>>,
+		member: &T::AccountId,
+	) {
+		let (delegate, shares) = match <DelegatedVotes<T>>::take((dao_id, proposal_id), member) {
+			Some(delegated) => delegated,
+			None => return,
+		};
+		if let Some(mut ballot) = Self::vote_of((dao_id, proposal_id), &delegate) {
+			let shares = shares.min(ballot.shares);
+			ballot.shares -= shares;
+			let tally = Self::tally_mut(proposal, ballot.vote);
+			*tally = tally.saturating_sub(shares);
+			if ballot.vote == Vote::Yes {
+				Self::set_yes_lock(dao_id, member, proposal_index, false);
+			}
+			<VotesByMember<T>>::insert((dao_id, proposal_id), &delegate, ballot);
+		}
+	}
+
+	/// Keep `member` from ragequitting until the proposal at `proposal_index` is processed if
+	/// their shares count as a yes vote on it, otherwise fall back to their yes vote before it.
+	fn set_yes_lock(dao_id: DaoId, member: &T::AccountId, proposal_index: ProposalIndex, yes: bool) {
+		<Members<T>>::mutate(dao_id, member, |m| if let Some(m) = m {
+			if yes {
+				if m.highest_index_yes_vote.map_or(true, |highest| proposal_index > highest) {
+					m.highest_index_yes_vote = Some(proposal_index);
+				}
+			} else if m.highest_index_yes_vote == Some(proposal_index) {
+				m.highest_index_yes_vote = Self::highest_yes_vote_before(dao_id, member, proposal_index);
+			}
+		});
+	}
+
+	/// Count the shares of every member delegating to `voter`, directly or through others, who
+	/// did not vote on the proposal, in the ballot of `voter`.
+	///
+	/// Delegations are followed up to `MaxDelegationDepth` levels and not past members who
+	/// voted, whose ballots count their own delegators. Shares counted by another member's
+	/// ballot move to the ballot of `voter`, and a yes `vote` locks them against ragequit like
+	/// the voter. Returns the delegated shares and the number of delegators visited.
+	fn collect_delegated_votes(
+		dao_id: DaoId,
+		proposal_index: ProposalIndex,
+		proposal_id: ProposalId,
+		proposal: &mut Proposal<T::AccountId, BalanceOf<T>>,
+		voter: &T::AccountId,
+		vote: Vote,
+	) -> (Shares, u32) {
+		let mut delegated: Shares = 0;
+		let mut visited = 0;
+		let mut level = vec![voter.clone()];
+		for _ in 0..T::MaxDelegationDepth::get() {
+			let mut next = Vec::new();
+			for delegate in level {
+				for delegator in Self::delegators(dao_id, &delegate) {
+					visited += 1;
+					if <VotesByMember<T>>::contains_key((dao_id, proposal_id), &delegator) {
+						continue;
+					}
+					Self::withdraw_delegated_vote(dao_id, proposal_index, proposal_id, proposal, &delegator);
+					let shares = Self::members(dao_id, &delegator)
+						.map_or(0, |m| Self::voting_shares(dao_id, proposal_id, &delegator, &m));
+					if shares > 0 {
+						<DelegatedVotes<T>>::insert((dao_id, proposal_id), &delegator, (voter.clone(), shares));
+						delegated = delegated.saturating_add(shares);
+						if vote == Vote::Yes {
+							Self::set_yes_lock(dao_id, &delegator, proposal_index, true);
+						}
+					}
+					next.push(delegator);
+				}
+			}
+			level = next;
+		}
+		(delegated, visited)
+	}
+
+	/// Move the ragequit locks of the members whose shares the ballot of `voter` counts to its
+	/// new `vote`, returning the number of delegators visited.
+	fn relock_delegators(
+		dao_id: DaoId,
+		proposal_index: ProposalIndex,
+		proposal_id: ProposalId,
+		voter: &T::AccountId,
+		vote: Vote,
+	) -> u32 {
+		let mut visited = 0;
+		let mut level = vec![voter.clone()];
+		for _ in 0..T::MaxDelegationDepth::get() {
+			let mut next = Vec::new();
+			for delegate in level {
+				for delegator in Self::delegators(dao_id, &delegate) {
+					visited += 1;
+					if <VotesByMember<T>>::contains_key((dao_id, proposal_id), &delegator) {
+						continue;
+					}
+					if Self::delegated_vote_of((dao_id, proposal_id), &delegator).map_or(false, |(d, _)| &d == voter) {
+						Self::set_yes_lock(dao_id, &delegator, proposal_index, vote == Vote::Yes);
+					}
+					next.push(delegator);
+				}
+			}
+			level = next;
+		}
+		visited
+	}
+
+	/// Highest queue index below `proposal_index` of an unprocessed proposal `member` voted yes on,
+	/// or whose yes ballot counts their delegated shares.
+	///
+	/// Proposals are processed in order, so the search stops at the first processed proposal.
+	fn highest_yes_vote_before(
+		dao_id: DaoId,
+		member: &T::AccountId,
+		proposal_index: ProposalIndex,
+	) -> Option<ProposalIndex> {
+		let mut index = proposal_index;
+		while let Some(previous) = index.checked_sub(1) {
+			index = previous;
+			let proposal_id = Self::proposal_queue(dao_id, index)?;
+			if Self::proposals(dao_id, proposal_id).map_or(true, |p| p.processed) {
+				return None;
+			}
+			let voter = Self::delegated_vote_of((dao_id, proposal_id), member)
+				.map_or_else(|| member.clone(), |(delegate, _)| delegate);
+			if Self::vote_of((dao_id, proposal_id), &voter).map(|ballot| ballot.vote) == Some(Vote::Yes) {
+				return Some(index);
+			}
+		}
+		None
+	}
+
+	/// Number of votes `member` submitted in the current period of `dao_id`.
+	pub fn votes_in_period(dao_id: DaoId, member: &T::AccountId) -> u32 {
+		let (period, votes) = <VotesInPeriod<T>>::get(dao_id, member);
+		if period == Self::current_period(dao_id) { votes } else { 0 }
+	}
+
+	/// Count a vote of `member` in the current period of `dao_id`, returning whether it pays its
+	/// fee: the first `MaxVotesPerPeriod` votes of a period do not.
+	fn count_vote(dao_id: DaoId, member: &T::AccountId) -> Pays {
+		let votes = Self::votes_in_period(dao_id, member);
+		<VotesInPeriod<T>>::insert(dao_id, member, (Self::current_period(dao_id), votes.saturating_add(1)));
+		if votes < T::MaxVotesPerPeriod::get() { Pays::No } else { Pays::Yes }
+	}
+
+	/// Store a new proposal to which `params` apply and emit `ProposalSubmitted`.
+	fn new_proposal(
+		dao_id: DaoId,
+		params: DaoParams<T::BlockNumber, BalanceOf<T>>,
+		proposal: Proposal<T::AccountId, BalanceOf<T>>,
+	) -> ProposalId {
+		let proposal_id = Self::proposal_count(dao_id);
+		ProposalCount::insert(dao_id, proposal_id + 1);
+		<ProposalParams<T>>::insert(dao_id, proposal_id, params);
+		Self::deposit_event(RawEvent::ProposalSubmitted(
+			dao_id,
+			proposal_id,
+			proposal.proposer.clone(),
+			proposal.applicant.clone(),
+		));
+		<Proposals<T>>::insert(dao_id, proposal_id, proposal);
+		proposal_id
+	}
+
+	/// Check that the proposal at `proposal_index` may be processed now.
+	fn ensure_processable(
+		dao_id: DaoId,
+		params: &DaoParams<T::BlockNumber, BalanceOf<T>>,
+		proposal_index: ProposalIndex,
+		proposal: &Proposal<T::AccountId, BalanceOf<T>>,
+	) -> DispatchResult {
+		ensure!(!proposal.processed, Error::<T>::AlreadyProcessed);
+		ensure!(
+			Self::current_period(dao_id) >= proposal.starting_period
+				+ params.voting_period_length
+				+ params.reveal_period_length
+				+ params.grace_period_length,
+			Error::<T>::ProposalNotReady
+		);
+		let previous_processed = proposal_index.checked_sub(1)
+			.and_then(|index| Self::proposal_queue(dao_id, index))
+			.and_then(|id| Self::proposals(dao_id, id))
+			.map_or(true, |p| p.processed);
+		ensure!(previous_processed, Error::<T>::PreviousProposalUnprocessed);
+		Ok(())
+	}
+
+	/// The kind of the proposal `proposal_id`, which must not be processed yet.
+	pub fn kind_of(
+		dao_id: DaoId,
+		proposal_id: ProposalId,
+		proposal: &Proposal<T::AccountId, BalanceOf<T>>,
+	) -> ProposalKind {
+		if proposal.guild_kick {
+			ProposalKind::GuildKick
+		} else if <ProposalActions<T>>::contains_key(dao_id, proposal_id) {
+			ProposalKind::Action
+		} else if <ParamChanges<T>>::contains_key(dao_id, proposal_id) {
+			ProposalKind::ParameterChange
+		} else if proposal.shares_requested > 0 || proposal.loot_requested > 0 {
+			ProposalKind::Membership
+		} else {
+			ProposalKind::Funding
+		}
+	}
+
+	/// Whether a proposal of `kind` whose voting has ended passes.
+	///
+	/// Abstain votes count towards the quorum of the proposal's kind, the approval threshold only
+	/// considers yes and no votes and the weight conviction adds to them. Besides needing both, a proposal fails if the
+	/// guild shrank by more than the DAO's dilution bound since a yes vote, if it asks for more
+	/// than the guild bank holds, if it would overflow total shares, if its applicant already
+	/// holds `MaxGrants` grants and the payment vests, or if its applicant has been kicked.
+	fn did_pass(
+		dao_id: DaoId,
+		proposal_id: ProposalId,
+		params: &DaoParams<T::BlockNumber, BalanceOf<T>>,
+		kind: ProposalKind,
+		proposal: &Proposal<T::AccountId, BalanceOf<T>>,
+	) -> bool {
+		let threshold = params.thresholds.of(kind);
+		let (yes_conviction, no_conviction) = Self::conviction_tally(dao_id, proposal_id);
+		let yes = proposal.yes_votes.saturating_add(yes_conviction);
+		let no = proposal.no_votes.saturating_add(no_conviction);
+		if yes <= Self::portion(threshold.approval, yes.saturating_add(no)) {
+			return false;
+		}
+		let votes_for_or_against = proposal.yes_votes.saturating_add(proposal.no_votes);
+		let votes = match params.voting_mode {
+			VotingMode::ShareWeighted => votes_for_or_against.saturating_add(proposal.abstain_votes),
+			VotingMode::Quadratic => Self::committed_shares(dao_id, proposal_id),
+		};
+		let total_shares = Self::proposal_snapshot(dao_id, proposal_id)
+			.map_or_else(|| Self::total_shares(dao_id), |(_, total)| total);
+		if votes < Self::portion(threshold.quorum, total_shares) {
+			return false;
+		}
+		if Self::total_shares_and_loot(dao_id).saturating_mul(params.dilution_bound)
+			< proposal.max_total_shares_and_loot_at_yes_vote
+		{
+			return false;
+		}
+		if proposal.payment_requested > Self::guild_balance(dao_id) {
+			return false;
+		}
+		if <ProposalVesting<T>>::contains_key(dao_id, proposal_id)
+			&& !Self::has_room_for_grant(dao_id, &proposal.applicant)
+		{
+			return false;
+		}
+		let requested = proposal.shares_requested.saturating_add(proposal.loot_requested);
+		if Self::total_shares_and_loot(dao_id).checked_add(requested).is_none() {
+			return false;
+		}
+		!Self::is_jailed(dao_id, &proposal.applicant)
+	}
+
+	/// Give `who` shares and loot, making them a member if they are not one yet.
+	fn mint(dao_id: DaoId, who: &T::AccountId, shares: Shares, loot: Shares) {
+		let mut member = match Self::members(dao_id, who) {
+			Some(member) => member,
+			None => {
+				// An account that becomes a member reclaims its address from any member that
+				// used it as a delegate key.
+				if let Some(other) = Self::member_by_delegate_key(dao_id, who) {
+					<Members<T>>::mutate(dao_id, &other, |m| if let Some(m) = m {
+						m.delegate_key = other.clone();
+					});
+					<MemberByDelegateKey<T>>::insert(dao_id, &other, &other);
+				}
+				<MemberByDelegateKey<T>>::insert(dao_id, who, who);
+				Member { delegate_key: who.clone(), ..Default::default() }
+			}
+		};
+		member.shares = member.shares.saturating_add(shares);
+		member.loot = member.loot.saturating_add(loot);
+		if shares > 0 {
+			Self::checkpoint(dao_id, who, member.shares);
+		}
+		<Members<T>>::insert(dao_id, who, member);
+		TotalShares::mutate(dao_id, |t| *t = t.saturating_add(shares));
+		TotalLoot::mutate(dao_id, |t| *t = t.saturating_add(loot));
+	}
+
+	/// Record that `who` holds `shares` from the current block on.
+	///
+	/// Checkpoints before the one in effect at the oldest snapshot are dropped. Past
+	/// `MaxCheckpoints`, the two oldest are merged into the lower of their shares, so that votes
+	/// never count shares the member did not hold.
+	fn checkpoint(dao_id: DaoId, who: &T::AccountId, shares: Shares) {
+		let now = <system::Module<T>>::block_number();
+		let oldest = Self::oldest_snapshot(dao_id);
+		<ShareCheckpoints<T>>::mutate(dao_id, who, |checkpoints| {
+			match checkpoints.last_mut() {
+				Some((block, held)) if *block == now => *held = shares,
+				_ => checkpoints.push((now, shares)),
+			}
+			if let Some(oldest) = oldest {
+				if let Some(in_effect) = checkpoints.iter().rposition(|(block, _)| *block <= oldest) {
+					checkpoints.drain(..in_effect);
+				}
+			}
+			while checkpoints.len() as u32 > T::MaxCheckpoints::get().max(2) {
+				let (_, held) = checkpoints.remove(1);
+				checkpoints[0].1 = checkpoints[0].1.min(held);
+			}
+		});
+	}
+
+	/// Oldest block whose shares votes may still be weighed with: the snapshot of the oldest
+	/// unprocessed proposal or of the fast-track pause vote, a voting period back while votes on
+	/// milestones are open, or the block before the current one if there are none of these.
+	/// `None` if the oldest unprocessed proposal was sponsored before snapshots were taken.
+	fn oldest_snapshot(dao_id: DaoId) -> Option<T::BlockNumber> {
+		let now = <system::Module<T>>::block_number();
+		let mut oldest = match Self::proposal_queue(dao_id, Self::processed_queue_length(dao_id)) {
+			Some(proposal_id) => Self::proposal_snapshot(dao_id, proposal_id)?.0,
+			None => now.saturating_sub(One::one()),
+		};
+		if let Some((block, _)) = Self::pause_snapshot(dao_id) {
+			oldest = oldest.min(block);
+		}
+		// Votes on milestones end a voting period after their snapshot.
+		if <MilestoneSnapshot<T>>::iter_prefix(dao_id).next().is_some() {
+			let voting_blocks = Self::dao(dao_id).map_or_else(Zero::zero, |params| Self::voting_blocks(&params));
+			oldest = oldest.min(now.saturating_sub(voting_blocks));
+		}
+		Some(oldest)
+	}
+
+	/// Shares `who` held in `dao_id` at the end of `block`, as far back as checkpoints are kept.
+	pub fn shares_at(dao_id: DaoId, who: &T::AccountId, block: T::BlockNumber) -> Shares {
+		let checkpoints = Self::share_checkpoints(dao_id, who);
+		match checkpoints.binary_search_by_key(&block, |(changed, _)| *changed) {
+			Ok(i) => checkpoints[i].1,
+			Err(0) => 0,
+			Err(i) => checkpoints[i - 1].1,
+		}
+	}
+
+	/// Shares of `member` that count on the proposal `proposal_id`: those they held when it was
+	/// sponsored, as far as they still hold them.
+	fn voting_shares(
+		dao_id: DaoId,
+		proposal_id: ProposalId,
+		who: &T::AccountId,
+		member: &Member<T::AccountId>,
+	) -> Shares {
+		match Self::proposal_snapshot(dao_id, proposal_id) {
+			Some((block, _)) => member.shares.min(Self::shares_at(dao_id, who, block)),
+			None => member.shares,
+		}
+	}
+
+	/// Kick `who` from the guild, converting their shares to loot and revoking their grants.
+	fn jail(dao_id: DaoId, who: &T::AccountId) {
+		<Members<T>>::mutate(dao_id, who, |m| if let Some(m) = m {
+			m.jailed = true;
+			TotalShares::mutate(dao_id, |t| *t = t.saturating_sub(m.shares));
+			TotalLoot::mutate(dao_id, |t| *t = t.saturating_add(m.shares));
+			m.loot = m.loot.saturating_add(m.shares);
+			m.shares = 0;
+			Self::checkpoint(dao_id, who, 0);
+		});
+		Self::revoke_grants(dao_id, who);
+		Self::lower_milestone_support(dao_id, who, 0);
+		Self::lower_pause_support(dao_id, who, 0);
+	}
+
+	/// Burn shares and loot of `who`, crediting their fair share of the guild bank.
+	fn do_ragequit(
+		dao_id: DaoId,
+		who: &T::AccountId,
+		mut member: Member<T::AccountId>,
+		shares_to_burn: Shares,
+		loot_to_burn: Shares,
+	) -> DispatchResult {
+		ensure!(Self::can_ragequit(dao_id, &member), Error::<T>::CannotRagequitYet);
+
+		let initial_total = Self::total_shares_and_loot(dao_id);
+		let amount = Self::fair_share(
+			Self::guild_balance(dao_id),
+			shares_to_burn.saturating_add(loot_to_burn),
+			initial_total,
+		);
+
+		member.shares -= shares_to_burn;
+		member.loot -= loot_to_burn;
+		if shares_to_burn > 0 {
+			Self::checkpoint(dao_id, who, member.shares);
+			Self::lower_milestone_support(dao_id, who, member.shares);
+			Self::lower_pause_support(dao_id, who, member.shares);
+		}
+		<Members<T>>::insert(dao_id, who, member);
+		TotalShares::mutate(dao_id, |t| *t = t.saturating_sub(shares_to_burn));
+		TotalLoot::mutate(dao_id, |t| *t = t.saturating_sub(loot_to_burn));
+		Self::internal_transfer(dao_id, Account::Guild, Account::User(who.clone()), amount);
+
+		Self::deposit_event(RawEvent::Ragequit(dao_id, who.clone(), shares_to_burn, loot_to_burn));
+		Ok(())
+	}
+
+	/// `part` of `shares`, rounded down.
+	fn portion(part: Perbill, shares: Shares) -> Shares {
+		multiply_by_rational(shares, part.deconstruct().into(), Perbill::one().deconstruct().into())
+			.unwrap_or(shares)
+	}
+
+	/// `balance * shares / total_shares`, rounded down.
+	fn fair_share(balance: BalanceOf<T>, shares: Shares, total_shares: Shares) -> BalanceOf<T> {
+		if total_shares.is_zero() {
+			return Zero::zero();
+		}
+		multiply_by_rational(balance.saturated_into::<u128>(), shares, total_shares)
+			.map(|amount| amount.saturated_into())
+			.unwrap_or_else(|_| Zero::zero())
+	}
+
+	/// Move `amount` between internal balances of the guild bank of `dao_id`.
+	fn internal_transfer(
+		dao_id: DaoId,
+		from: Account<T::AccountId>,
+		to: Account<T::AccountId>,
+		amount: BalanceOf<T>,
+	) {
+		if amount.is_zero() {
+			return;
+		}
+		Self::mutate_balance(dao_id, from, |b| *b = b.saturating_sub(amount));
+		Self::mutate_balance(dao_id, to, |b| *b = b.saturating_add(amount));
+	}
+
+	fn mutate_balance(dao_id: DaoId, account: Account<T::AccountId>, f: impl FnOnce(&mut BalanceOf<T>)) {
+		match account {
+			Account::Guild => <GuildBalance<T>>::mutate(dao_id, f),
+			Account::Escrow => <EscrowBalance<T>>::mutate(dao_id, f),
+			Account::Vesting => <VestingBalance<T>>::mutate(dao_id, f),
+			Account::Milestones => <MilestoneBalance<T>>::mutate(dao_id, f),
+			Account::User(who) => <UserBalances<T>>::mutate(dao_id, who, f),
+		}
+	}
+
+	/// Pay the processing reward out of the sponsor's deposit and return the rest.
+	fn return_deposit(
+		params: &DaoParams<T::BlockNumber, BalanceOf<T>>,
+		sponsor: &T::AccountId,
+		processor: &T::AccountId,
+	) {
+		let deposit = params.proposal_deposit;
+		let reward = params.processing_reward.min(deposit);
+		let reward_left = T::Currency::repatriate_reserved(sponsor, processor, reward, BalanceStatus::Free)
+			.unwrap_or(reward);
+		T::Currency::unreserve(sponsor, deposit.saturating_sub(reward).saturating_add(reward_left));
+	}
+}
+
+/// Credits imbalances, such as transaction fees or dust, to the guild bank of the genesis DAO.
+impl<T: Trait> OnUnbalanced<NegativeImbalanceOf<T>> for Module<T> {
+	fn on_nonzero_unbalanced(amount: NegativeImbalanceOf<T>) {
+		let value = amount.peek();
+		// The guild bank is kept alive, so the imbalance is never dropped.
+		T::Currency::resolve_creating(&Self::account_id(GENESIS_DAO), amount);
+		<GuildBalance<T>>::mutate(GENESIS_DAO, |b| *b = b.saturating_add(value));
+	}
+}
+
+/// Validates calls to the DAO before they enter the transaction pool.
+///
+/// Members' votes are refunded their fees up to `MaxVotesPerPeriod` per period, so this only
+/// accepts votes from the delegate key of a member that can vote on the proposal right now, and
+/// at most as many votes per member and period as are refunded.
+/// Calls a paused DAO would reject are kept out of the pool as well. Other calls pass through
+/// untouched.
+///
+/// Like an account nonce, a vote carries its number among the member's votes in the current
+/// period, starting at 0. A vote provides its number and requires the one before it, so the pool
+/// orders a member's votes and never holds more of them than the period has room for.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct ValidateDaoCall<T: Trait + Send + Sync>(#[codec(compact)] u32, PhantomData<T>);
+
+impl<T: Trait + Send + Sync> ValidateDaoCall<T> {
+	/// Create new `SignedExtension` to validate DAO calls, numbering a vote as the member's
+	/// first in the period.
+	pub fn new() -> Self {
+		Self::from(0)
+	}
+
+	/// Create new `SignedExtension` to validate DAO calls, numbering a vote `vote_number` among
+	/// the member's votes in the period.
+	pub fn from(vote_number: u32) -> Self {
+		Self(vote_number, PhantomData)
+	}
+
+	/// The member voting with `who` and the number of votes they cast in the period so far if
+	/// the vote is valid, or why it is not.
+	fn validate_vote(
+		&self,
+		dao_id: DaoId,
+		who: &T::AccountId,
+		proposal_index: ProposalIndex,
+		vote: Vote,
+		mode: VotingMode,
+	) -> Result<(T::AccountId, u32), TransactionValidityError> {
+		let (member, ..) = <Module<T>>::ensure_can_vote(dao_id, who, proposal_index, vote, mode)
+			.map_err(|e| InvalidTransaction::Custom(e.as_u8()))?;
+		ensure!(self.0 < T::MaxVotesPerPeriod::get(), InvalidTransaction::Custom(Error::<T>::TooManyVotes.as_u8()));
+		let votes = <Module<T>>::votes_in_period(dao_id, &member);
+		ensure!(self.0 >= votes, InvalidTransaction::Stale);
+		Ok((member, votes))
+	}
+}
+
+impl<T: Trait + Send + Sync> ValidateDaoCall<T> where
+	<T as system::Trait>::Call: IsSubType<Module<T>, T>
+{
+	/// The DAO, queue index, vote and voting mode of `call` if it is a vote.
+	fn vote_in(call: &<T as system::Trait>::Call) -> Option<(DaoId, ProposalIndex, Vote, VotingMode)> {
+		match call.is_sub_type() {
+			Some(Call::submit_vote(dao_id, proposal_index, vote)) =>
+				Some((*dao_id, *proposal_index, *vote, VotingMode::ShareWeighted)),
+			Some(Call::submit_conviction_vote(dao_id, proposal_index, vote, _)) =>
+				Some((*dao_id, *proposal_index, *vote, VotingMode::ShareWeighted)),
+			Some(Call::submit_quadratic_vote(dao_id, proposal_index, vote, _)) =>
+				Some((*dao_id, *proposal_index, *vote, VotingMode::Quadratic)),
+			_ => None,
+		}
+	}
+
+	/// Reject `call` if a paused DAO would reject it.
+	fn ensure_not_paused(call: &<T as system::Trait>::Call) -> Result<(), TransactionValidityError> {
+		let dao_id = match call.is_sub_type() {
+			Some(Call::submit_proposal(dao_id, ..))
+			| Some(Call::submit_vesting_proposal(dao_id, ..))
+			| Some(Call::submit_milestone_proposal(dao_id, ..))
+			| Some(Call::submit_guild_kick_proposal(dao_id, ..))
+			| Some(Call::submit_action_proposal(dao_id, ..))
+			| Some(Call::submit_params_proposal(dao_id, ..))
+			| Some(Call::sponsor_proposal(dao_id, ..))
+			| Some(Call::process_proposal(dao_id, ..))
+			| Some(Call::execute_timelocked(dao_id, ..))
+			| Some(Call::approve_milestone(dao_id, ..)) => *dao_id,
+			_ => return Ok(()),
+		};
+		<Module<T>>::ensure_not_paused(dao_id).map_err(|e| InvalidTransaction::Custom(e.as_u8()).into())
+	}
+}
+
+impl<T: Trait + Send + Sync> Default for ValidateDaoCall<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Trait + Send + Sync> sp_std::fmt::Debug for ValidateDaoCall<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "ValidateDaoCall")
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for ValidateDaoCall<T> where
+	<T as system::Trait>::Call: IsSubType<Module<T>, T>
+{
+	const IDENTIFIER: &'static str = "ValidateDaoCall";
+	type AccountId = T::AccountId;
+	type Call = <T as system::Trait>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		Self::ensure_not_paused(call)?;
+		match Self::vote_in(call) {
+			Some((dao_id, proposal_index, vote, mode)) => {
+				let (member, votes) = self.validate_vote(dao_id, who, proposal_index, vote, mode)?;
+				let period = <Module<T>>::current_period(dao_id);
+				let requires = if self.0 > votes {
+					vec![(b"dao/votes", dao_id, &member, period, self.0 - 1).encode()]
+				} else {
+					vec![]
+				};
+				Ok(ValidTransaction {
+					requires,
+					// A member's equal votes on a proposal conflict, whichever key they are sent
+					// with. Different votes do not, the later one changes the earlier. Votes with
+					// the same number conflict as well.
+					provides: vec![
+						(b"dao/vote", dao_id, &member, proposal_index, vote).encode(),
+						(b"dao/votes", dao_id, &member, period, self.0).encode(),
+					],
+					..Default::default()
+				})
+			}
+			_ => Ok(Default::default()),
+		}
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> Result<(), TransactionValidityError> {
+		Self::ensure_not_paused(call)?;
+		if let Some((dao_id, proposal_index, vote, mode)) = Self::vote_in(call) {
+			let (_, votes) = self.validate_vote(dao_id, who, proposal_index, vote, mode)?;
+			ensure!(self.0 == votes, InvalidTransaction::Future);
+		}
+		Ok(())
+	}
+}
+
+/// Ensures that the origin is any DAO, dispatching the call of a passed action proposal, and
+/// returns the DAO's id.
+pub struct EnsureDao;
+
+impl<O: Into<Result<RawOrigin, O>> + From<RawOrigin>> EnsureOrigin<O> for EnsureDao {
+	type Success = DaoId;
+
+	fn try_origin(o: O) -> Result<Self::Success, O> {
+		o.into().map(|o| match o {
+			RawOrigin::Dao(dao_id) => dao_id,
+		})
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn successful_origin() -> O {
+		O::from(RawOrigin::Dao(GENESIS_DAO))
+	}
+}
+
+/// Ensures that the origin is the DAO `Id`, dispatching the call of a passed action proposal.
+pub struct EnsureDaoId<Id>(PhantomData<Id>);
+
+impl<O: Into<Result<RawOrigin, O>> + From<RawOrigin>, Id: Get<DaoId>> EnsureOrigin<O> for EnsureDaoId<Id> {
+	type Success = ();
+
+	fn try_origin(o: O) -> Result<Self::Success, O> {
+		o.into().and_then(|o| match o {
+			RawOrigin::Dao(dao_id) if dao_id == Id::get() => Ok(()),
+			o => Err(O::from(o)),
+		})
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn successful_origin() -> O {
+		O::from(RawOrigin::Dao(Id::get()))
 	}
 }
@@ -0,0 +1,55 @@
+//! Storage migrations between the layouts listed in `Releases`.
+//!
+//! Each step upgrades storage by exactly one version and reports the weight it consumed.
+//! `migrate` runs every step newer than the version found on chain, in order, so a chain can
+//! skip any number of runtime upgrades without losing member records.
+
+use super::*;
+use frame_support::{StorageValue, storage::migration::take_storage_value};
+
+const MODULE: &[u8] = b"TemplateModule";
+
+/// Upgrade storage from the layout found on chain to the current one.
+pub fn migrate<T: Trait>() -> Weight {
+	let mut weight = T::DbWeight::get().reads(1);
+
+	if StorageVersion::get() == Releases::V0 {
+		weight = weight.saturating_add(v0_to_v1::<T>());
+	}
+
+	weight
+}
+
+/// Replace the node template's `Something` value with the genesis DAO.
+///
+/// The template never stored members, so nothing is carried over: the dummy value is removed
+/// and the genesis DAO is summoned at the current block with the parameters of the pallet's
+/// `Trait` and `Trait::TemplateSummoners` as members. Like the sender of `summon`, the first
+/// summoner endows the guild bank with the existential deposit so that withdrawals can keep it
+/// alive. If they cannot, the bank is left to be created by the first transfer into it.
+fn v0_to_v1<T: Trait>() -> Weight {
+	let _ = take_storage_value::<u32>(MODULE, b"Something", &[]);
+
+	let summoners: Vec<_> = T::TemplateSummoners::get().into_iter()
+		.filter(|(_, shares)| *shares > 0)
+		.collect();
+	<Module<T>>::create_dao(<Module<T>>::genesis_params(), &summoners);
+
+	let bank = <Module<T>>::account_id(GENESIS_DAO);
+	if let Some((funder, _)) = summoners.first() {
+		if T::Currency::total_balance(&bank).is_zero() {
+			let ed = T::Currency::minimum_balance();
+			let _ = T::Currency::transfer(funder, &bank, ed, ExistenceRequirement::KeepAlive);
+		}
+	}
+
+	StorageVersion::put(Releases::V1);
+
+	// Reads of the summoners, the dummy value, the DAO count and both accounts, and per summoner
+	// of their member record, delegate key, checkpoints, the queue head, pause snapshot, open
+	// milestone votes and parameters their checkpoint keeps, and the totals; writes of the dummy value, the DAO count, parameters and
+	// summoning time, both accounts and the version, and per summoner of their member record,
+	// delegate key, checkpoints and the totals.
+	let summoners = summoners.len() as Weight;
+	T::DbWeight::get().reads_writes(5 + 9 * summoners, 7 + 5 * summoners)
+}
@@ -1,17 +1,76 @@
 // Creating mock runtime here
 
-use crate::{Module, Trait};
+use crate::{Module, Trait, GenesisConfig, GuildBalance, Shares, DaoId, EnsureDao, GENESIS_DAO};
 use sp_core::H256;
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use frame_support::{
+	impl_outer_origin, impl_outer_event, impl_outer_dispatch, parameter_types, StorageMap,
+	traits::{Currency, Get, OnFinalize, OnInitialize},
+	weights::{Weight, RuntimeDbWeight},
+};
 use sp_runtime::{
-	traits::{BlakeTwo256, IdentityLookup}, testing::Header, Perbill,
+	traits::{BlakeTwo256, IdentityLookup}, testing::Header, ModuleId, Perbill,
 };
 use frame_system as system;
 
 impl_outer_origin! {
-	pub enum Origin for Test {}
+	pub enum Origin for Test {
+		template,
+	}
+}
+
+mod template {
+	pub use crate::{Event, Origin};
 }
 
+impl_outer_event! {
+	pub enum TestEvent for Test {
+		system<T>,
+		pallet_balances<T>,
+		template<T>,
+	}
+}
+
+impl_outer_dispatch! {
+	pub enum Call for Test where origin: Origin {
+		template::TemplateModule,
+		logger::Logger,
+	}
+}
+
+/// A pallet whose only call must be dispatched by the DAO, to test action proposals.
+pub mod logger {
+	use super::*;
+	use frame_support::{decl_module, dispatch::DispatchResult, traits::EnsureOrigin};
+	use std::cell::RefCell;
+
+	thread_local! {
+		/// Values logged by DAOs, in order.
+		pub static LOG: RefCell<Vec<(DaoId, u32)>> = RefCell::new(Vec::new());
+	}
+
+	pub trait Trait: system::Trait<Origin = super::Origin> {}
+
+	decl_module! {
+		pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+			#[weight = *weight]
+			fn log(origin, i: u32, weight: Weight) -> DispatchResult {
+				let dao_id = EnsureDao::ensure_origin(origin)?;
+				LOG.with(|log| log.borrow_mut().push((dao_id, i)));
+				Ok(())
+			}
+		}
+	}
+
+	/// The values logged so far, with the DAO that logged them.
+	pub fn log() -> Vec<(DaoId, u32)> {
+		LOG.with(|log| log.borrow().clone())
+	}
+}
+
+/// Wide enough for the guild banks of different DAOs, which are sub-accounts of the module id,
+/// to be distinct accounts.
+pub type AccountId = u128;
+
 // For testing the pallet, we construct most of a mock runtime. This means
 // first constructing a configuration type (`Test`) which `impl`s each of the
 // configuration traits of pallets we want to use.
@@ -22,21 +81,22 @@ parameter_types! {
 	pub const MaximumBlockWeight: Weight = 1024;
 	pub const MaximumBlockLength: u32 = 2 * 1024;
 	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+	pub const DbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 1, write: 10 };
 }
 impl system::Trait for Test {
 	type Origin = Origin;
-	type Call = ();
+	type Call = Call;
 	type Index = u64;
 	type BlockNumber = u64;
 	type Hash = H256;
 	type Hashing = BlakeTwo256;
-	type AccountId = u64;
+	type AccountId = AccountId;
 	type Lookup = IdentityLookup<Self::AccountId>;
 	type Header = Header;
-	type Event = ();
+	type Event = TestEvent;
 	type BlockHashCount = BlockHashCount;
 	type MaximumBlockWeight = MaximumBlockWeight;
-	type DbWeight = ();
+	type DbWeight = DbWeight;
 	type BlockExecutionWeight = ();
 	type ExtrinsicBaseWeight = ();
 	type MaximumExtrinsicWeight = MaximumBlockWeight;
@@ -44,17 +104,172 @@ impl system::Trait for Test {
 	type AvailableBlockRatio = AvailableBlockRatio;
 	type Version = ();
 	type ModuleToIndex = ();
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 }
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+impl pallet_balances::Trait for Test {
+	type Balance = u64;
+	type Event = TestEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+}
+parameter_types! {
+	pub const DaoModuleId: ModuleId = ModuleId(*b"py/moloc");
+	pub const PeriodDuration: u64 = 1;
+	pub const VotingPeriodLength: u64 = 2;
+	pub const GracePeriodLength: u64 = 1;
+	pub const ProposalDeposit: u64 = 10;
+	pub const ProcessingReward: u64 = 1;
+	pub const DilutionBound: u128 = 3;
+	pub const MaxVotesPerPeriod: u32 = 1;
+	pub const MaxActionWeight: Weight = 100;
+	pub const MaxDelegationDepth: u32 = 2;
+	pub const MaxDelegators: u32 = 2;
+	pub const MaxCommitments: u32 = 2;
+	pub const MaxGuardians: u32 = 3;
+	pub const MaxGrants: u32 = 2;
+	pub const MaxMilestones: u32 = 3;
+	pub const MaxMilestoneApprovals: u32 = 2;
+	pub const MaxCheckpoints: u32 = 3;
+}
 impl Trait for Test {
-	type Event = ();
+	type Event = TestEvent;
+	type Origin = Origin;
+	type Proposal = Call;
+	type MaxActionWeight = MaxActionWeight;
+	type Currency = Balances;
+	type ModuleId = DaoModuleId;
+	type PeriodDuration = PeriodDuration;
+	type VotingPeriodLength = VotingPeriodLength;
+	type GracePeriodLength = GracePeriodLength;
+	type ProposalDeposit = ProposalDeposit;
+	type ProcessingReward = ProcessingReward;
+	type DilutionBound = DilutionBound;
+	type Thresholds = ();
+	type MaxVotesPerPeriod = MaxVotesPerPeriod;
+	type MaxDelegationDepth = MaxDelegationDepth;
+	type MaxDelegators = MaxDelegators;
+	type MaxCommitments = MaxCommitments;
+	type DaoOrigin = EnsureDao;
+	type MaxGuardians = MaxGuardians;
+	type MaxGrants = MaxGrants;
+	type MaxMilestones = MaxMilestones;
+	type MaxMilestoneApprovals = MaxMilestoneApprovals;
+	type MaxCheckpoints = MaxCheckpoints;
+	type TemplateSummoners = TemplateSummoners;
 }
+
+/// Summoners of the DAO created by the migration from the node template.
+pub struct TemplateSummoners;
+impl Get<Vec<(AccountId, Shares)>> for TemplateSummoners {
+	fn get() -> Vec<(AccountId, Shares)> {
+		vec![(1, 3), (2, 2)]
+	}
+}
+
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
 pub type TemplateModule = Module<Test>;
+impl logger::Trait for Test {}
+pub type Logger = logger::Module<Test>;
+
+/// Builds genesis storage for tests.
+///
+/// By default account 1 summons the genesis DAO with a single share, accounts 1 to 4 start with
+/// 100 units each and the guild bank only holds its existential deposit.
+pub struct ExtBuilder {
+	members: Vec<(AccountId, Shares)>,
+	balances: Vec<(AccountId, u64)>,
+	bank: u64,
+}
+
+impl Default for ExtBuilder {
+	fn default() -> Self {
+		ExtBuilder {
+			members: vec![(1, 1)],
+			balances: (1..=4).map(|who| (who, 100)).collect(),
+			bank: 0,
+		}
+	}
+}
+
+impl ExtBuilder {
+	/// Summon the genesis DAO with these members and shares.
+	pub fn members(mut self, members: Vec<(AccountId, Shares)>) -> Self {
+		self.members = members;
+		self
+	}
+
+	/// Endow accounts with these free balances.
+	pub fn balances(mut self, balances: Vec<(AccountId, u64)>) -> Self {
+		self.balances = balances;
+		self
+	}
+
+	/// Start with `amount` in the guild balance, on top of the bank's existential deposit.
+	pub fn bank(mut self, amount: u64) -> Self {
+		self.bank = amount;
+		self
+	}
+
+	pub fn build(self) -> sp_io::TestExternalities {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		pallet_balances::GenesisConfig::<Test> {
+			balances: self.balances,
+		}.assimilate_storage(&mut t).unwrap();
+		GenesisConfig::<Test> {
+			members: self.members,
+		}.assimilate_storage(&mut t).unwrap();
+
+		let mut ext: sp_io::TestExternalities = t.into();
+		let bank = self.bank;
+		ext.execute_with(|| {
+			System::set_block_number(1);
+			if bank > 0 {
+				let _ = Balances::deposit_creating(&TemplateModule::account_id(GENESIS_DAO), bank);
+				<GuildBalance<Test>>::insert(GENESIS_DAO, bank);
+			}
+		});
+		ext
+	}
+}
 
-// This function basically just builds a genesis storage key/value store according to
-// our desired mockup.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	ExtBuilder::default().build()
+}
+
+/// Finalize blocks until `n` is the current block, running every block hook on the way.
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		TemplateModule::on_finalize(System::block_number());
+		System::on_finalize(System::block_number());
+		System::set_block_number(System::block_number() + 1);
+		System::on_initialize(System::block_number());
+		TemplateModule::on_initialize(System::block_number());
+	}
+}
+
+/// Move `n` periods into the future.
+pub fn advance_periods(n: u64) {
+	run_to_block(System::block_number() + n * PeriodDuration::get());
+}
+
+/// The most recently deposited event.
+pub fn last_event() -> TestEvent {
+	System::events().pop().expect("an event was deposited").event
+}
+
+/// The most recently deposited event of this pallet.
+pub fn last_dao_event() -> crate::Event<Test> {
+	System::events().into_iter().rev()
+		.find_map(|record| match record.event {
+			TestEvent::template(event) => Some(event),
+			_ => None,
+		})
+		.expect("a DAO event was deposited")
 }
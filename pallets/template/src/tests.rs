@@ -1,26 +1,1583 @@
 // Tests to be written here
 
-use crate::{Error, mock::*};
-use frame_support::{assert_ok, assert_noop};
+use crate::{
+	Ballot, Conviction, DaoParams, EnsureDao, EnsureDaoId, Error, Member, Milestone, ProposalKind, RawEvent, RawOrigin,
+	Releases, StorageVersion, Threshold, Thresholds, ValidateDaoCall, VestingSchedule, Veto, Vote, VotingMode,
+	mock::*, Daos, Members, ProposalIndex, ProposalQueueLength, TotalShares, UserBalances,
+};
+use codec::Encode;
+use frame_support::{
+	assert_ok, assert_noop, assert_err, parameter_types, StorageMap, StorageValue,
+	dispatch::DispatchError,
+	storage::migration::{get_storage_value, put_storage_value},
+	traits::{Currency, EnsureOrigin, ExistenceRequirement, Get, OnRuntimeUpgrade, OnUnbalanced, WithdrawReason},
+	weights::{DispatchInfo, GetDispatchInfo, Pays},
+};
+use sp_runtime::{
+	Perbill,
+	traits::{BlakeTwo256, Hash, SignedExtension},
+	transaction_validity::{InvalidTransaction, TransactionValidity},
+};
+
+/// Submit a proposal from account 2 asking for `shares` in exchange for `tribute`, sponsor it
+/// with member 1 at the current block and return its queue index.
+fn sponsored_membership_proposal(shares: u128, tribute: u64) -> u64 {
+	let proposal_id = TemplateModule::proposal_count(0);
+	assert_ok!(TemplateModule::submit_proposal(Origin::signed(2), 0, 2, shares, 0, tribute, 0, vec![]));
+	assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, proposal_id));
+	TemplateModule::proposal_queue_length(0) - 1
+}
+
+#[test]
+fn genesis_summons_dao() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(TemplateModule::members(0, 1), Some(Member { delegate_key: 1, shares: 1, ..Default::default() }));
+		assert_eq!(TemplateModule::member_by_delegate_key(0, 1), Some(1));
+		assert_eq!(TemplateModule::total_shares(0), 1);
+		assert_eq!(Balances::free_balance(TemplateModule::account_id(0)), 1);
+		assert_eq!(StorageVersion::get(), Releases::V1);
+		assert_eq!(TemplateModule::dao_count(), 1);
+		assert_eq!(TemplateModule::dao(0).map(|p| p.period_duration), Some(PeriodDuration::get()));
+	});
+}
+
+/// Parameters of a DAO with two-block periods and a deposit of 4.
+fn params() -> DaoParams<u64, u64> {
+	DaoParams {
+		period_duration: 2,
+		voting_period_length: 1,
+		grace_period_length: 1,
+		proposal_deposit: 4,
+		processing_reward: 2,
+		dilution_bound: 3,
+		thresholds: Thresholds::default(),
+		voting_mode: VotingMode::ShareWeighted,
+		reveal_period_length: 0,
+		reveal_deposit: 0,
+		timelock_periods: 0,
+	}
+}
+
+#[test]
+fn summon_validates_params_and_summoners() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TemplateModule::summon(Origin::signed(2), DaoParams { period_duration: 0, ..params() }, vec![(2, 1)]),
+			Error::<Test>::InvalidParams
+		);
+		assert_noop!(
+			TemplateModule::summon(Origin::signed(2), DaoParams { processing_reward: 5, ..params() }, vec![(2, 1)]),
+			Error::<Test>::InvalidParams
+		);
+		assert_noop!(TemplateModule::summon(Origin::signed(2), params(), vec![]), Error::<Test>::NoSummoners);
+		assert_noop!(
+			TemplateModule::summon(Origin::signed(2), params(), vec![(2, 1), (3, 0)]),
+			Error::<Test>::NoSummoners
+		);
+		assert_noop!(
+			TemplateModule::submit_proposal(Origin::signed(2), 1, 2, 1, 0, 0, 0, vec![]),
+			Error::<Test>::UnknownDao
+		);
+	});
+}
+
+#[test]
+fn summoned_daos_are_independent() {
+	new_test_ext().execute_with(|| {
+		run_to_block(3);
+		assert_ok!(TemplateModule::summon(Origin::signed(2), params(), vec![(3, 5)]));
+		assert_eq!(last_dao_event(), RawEvent::Summoned(1, 2));
+		assert_eq!(TemplateModule::dao_count(), 2);
+		assert_eq!(TemplateModule::summoning_time(1), 3);
+		assert_eq!(TemplateModule::total_shares(1), 5);
+		// The summoner endows the new guild bank.
+		assert_eq!(Balances::free_balance(2), 99);
+		assert_eq!(Balances::free_balance(TemplateModule::account_id(1)), 1);
+		assert_ne!(TemplateModule::account_id(1), TemplateModule::account_id(0));
+
+		// Members of one DAO have no say in another.
+		assert_ok!(TemplateModule::submit_proposal(Origin::signed(4), 1, 4, 2, 0, 20, 0, vec![]));
+		assert_noop!(TemplateModule::sponsor_proposal(Origin::signed(1), 1, 0), Error::<Test>::NotDelegate);
+		assert_noop!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0), Error::<Test>::UnknownProposal);
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(3), 1, 0));
+		assert_eq!(Balances::reserved_balance(3), 4);
+		assert_eq!(TemplateModule::escrow_balance(1), 20);
+		assert_eq!(TemplateModule::escrow_balance(0), 0);
+
+		// DAO 1 counts periods of two blocks from block 3.
+		assert_eq!(TemplateModule::current_period(1), 0);
+		run_to_block(5);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(3), 1, 0, Vote::Yes));
+		run_to_block(9);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(1), 1, 0));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(1, 0, 0, true));
+		assert_eq!(TemplateModule::members(1, 4).map(|m| m.shares), Some(2));
+		assert_eq!(TemplateModule::members(0, 4), None);
+		assert_eq!(TemplateModule::guild_balance(1), 20);
+		assert_eq!(TemplateModule::guild_balance(0), 0);
+		assert_eq!(Balances::free_balance(1), 102);
+
+		// Each DAO dispatches actions with its own origin.
+		let action = Box::new(Call::Logger(logger::Call::log(7, 0)));
+		assert_ok!(TemplateModule::submit_action_proposal(Origin::signed(3), 1, action, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(3), 1, 1));
+		run_to_block(11);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(3), 1, 1, Vote::Yes));
+		run_to_block(15);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(1), 1, 1));
+		assert_eq!(logger::log(), vec![(1, 7)]);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn thresholds_depend_on_proposal_kind() {
+	new_test_ext().execute_with(|| {
+		// Actions need two thirds of the votes, guild kicks need every share to vote.
+		let thresholds = Thresholds {
+			action: Threshold { approval: Perbill::from_percent(67), quorum: Perbill::zero() },
+			guild_kick: Threshold { approval: Perbill::zero(), quorum: Perbill::one() },
+			..Default::default()
+		};
+		run_to_block(3);
+		assert_ok!(TemplateModule::summon(
+			Origin::signed(2),
+			DaoParams { thresholds, ..params() },
+			vec![(1, 2), (3, 1)],
+		));
+
+		assert_ok!(TemplateModule::submit_proposal(Origin::signed(4), 1, 4, 1, 0, 0, 0, vec![]));
+		let action = Box::new(Call::Logger(logger::Call::log(9, 0)));
+		assert_ok!(TemplateModule::submit_action_proposal(Origin::signed(3), 1, action, vec![]));
+		assert_ok!(TemplateModule::submit_guild_kick_proposal(Origin::signed(1), 1, 3, vec![]));
+		for proposal_id in 0..3 {
+			assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 1, proposal_id));
+		}
+		assert_eq!(TemplateModule::kind_of(1, 0, &TemplateModule::proposals(1, 0).unwrap()), ProposalKind::Membership);
+		assert_eq!(TemplateModule::kind_of(1, 1, &TemplateModule::proposals(1, 1).unwrap()), ProposalKind::Action);
+		assert_eq!(TemplateModule::kind_of(1, 2, &TemplateModule::proposals(1, 2).unwrap()), ProposalKind::GuildKick);
+
+		// Two of three shares approve each proposal, voting starts one period after another.
+		for (index, block) in [(0, 5), (1, 7)].iter() {
+			run_to_block(*block);
+			assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 1, *index, Vote::Yes));
+			assert_ok!(TemplateModule::submit_vote(Origin::signed(3), 1, *index, Vote::No));
+		}
+		run_to_block(9);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 1, 2, Vote::Yes));
+
+		run_to_block(13);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(2), 1, 0));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(1, 0, 0, true));
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(2), 1, 1));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(1, 1, 1, false));
+		assert_eq!(logger::log(), vec![]);
+		// A unanimous kick fails without quorum.
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(2), 1, 2));
+		assert_eq!(last_dao_event(), RawEvent::GuildKickProcessed(1, 2, 2, false));
+		assert_eq!(TemplateModule::members(1, 3).map(|m| m.shares), Some(1));
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+parameter_types! {
+	pub const SecondDao: u32 = 1;
+}
+
+#[test]
+fn dao_origins_are_told_apart() {
+	assert_eq!(EnsureDao::try_origin(Origin::from(RawOrigin::Dao(1))).ok(), Some(1));
+	assert!(EnsureDao::try_origin(Origin::signed(1)).is_err());
+	assert!(EnsureDaoId::<SecondDao>::try_origin(Origin::from(RawOrigin::Dao(1))).is_ok());
+	assert!(EnsureDaoId::<SecondDao>::try_origin(Origin::from(RawOrigin::Dao(0))).is_err());
+}
+
+#[test]
+fn passed_proposal_mints_shares_and_moves_tribute_to_guild() {
+	new_test_ext().execute_with(|| {
+		let index = sponsored_membership_proposal(10, 50);
+		assert_eq!(Balances::reserved_balance(1), 10);
+		assert_eq!(TemplateModule::escrow_balance(0), 50);
+
+		// Voting starts in the period after sponsorship.
+		assert_noop!(
+			TemplateModule::submit_vote(Origin::signed(1), 0, index, Vote::Yes),
+			Error::<Test>::VotingNotStarted
+		);
+		advance_periods(1);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, index, Vote::Yes));
+		assert_eq!(last_dao_event(), RawEvent::VoteSubmitted(0, index, 1, Vote::Yes));
+		assert_noop!(
+			TemplateModule::submit_vote(Origin::signed(1), 0, index, Vote::Yes),
+			Error::<Test>::AlreadyVoted
+		);
+
+		// Two periods of voting, one of grace.
+		advance_periods(2);
+		assert_noop!(
+			TemplateModule::process_proposal(Origin::signed(3), 0, index),
+			Error::<Test>::ProposalNotReady
+		);
+
+		advance_periods(1);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, index));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(0, index, 0, true));
+		assert_eq!(TemplateModule::members(0, 2).map(|m| m.shares), Some(10));
+		assert_eq!(TemplateModule::total_shares(0), 11);
+		assert_eq!(TemplateModule::guild_balance(0), 50);
+		assert_eq!(TemplateModule::escrow_balance(0), 0);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(1), 99);
+		assert_eq!(Balances::free_balance(3), 101);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn abstain_votes_count_towards_quorum_only() {
+	ExtBuilder::default().members(vec![(1, 1), (3, 2)]).build().execute_with(|| {
+		// Membership proposals need every share to vote.
+		<Daos<Test>>::mutate(0, |p| p.as_mut().unwrap().thresholds.membership.quorum = Perbill::one());
+		let first = sponsored_membership_proposal(1, 0);
+		let second = sponsored_membership_proposal(1, 0);
+		advance_periods(1);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, first, Vote::Yes));
+		advance_periods(1);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, second, Vote::Yes));
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(3), 0, second, Vote::Abstain));
+		assert_eq!(TemplateModule::proposals(0, 1).map(|p| p.abstain_votes), Some(2));
+
+		advance_periods(3);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(4), 0, first));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(0, first, 0, false));
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(4), 0, second));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(0, second, 1, true));
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn votes_can_change_while_voting_is_open() {
+	ExtBuilder::default().members(vec![(1, 1), (3, 2)]).build().execute_with(|| {
+		let first = sponsored_membership_proposal(1, 0);
+		let second = sponsored_membership_proposal(1, 0);
+		advance_periods(1);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, first, Vote::Yes));
+		advance_periods(1);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, second, Vote::Yes));
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(3), 0, second, Vote::No));
+		assert_eq!(TemplateModule::members(0, 1).unwrap().highest_index_yes_vote, Some(second));
+
+		// Taking back the yes vote on the second proposal leaves the first locking ragequit.
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, second, Vote::Abstain));
+		assert_eq!(last_dao_event(), RawEvent::VoteChanged(0, second, 1, Vote::Abstain));
+		let proposal = TemplateModule::proposals(0, 1).unwrap();
+		assert_eq!((proposal.yes_votes, proposal.no_votes, proposal.abstain_votes), (0, 2, 1));
+		assert_eq!(TemplateModule::members(0, 1).unwrap().highest_index_yes_vote, Some(first));
+		assert_noop!(
+			TemplateModule::submit_vote(Origin::signed(1), 0, second, Vote::Abstain),
+			Error::<Test>::AlreadyVoted
+		);
+
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, first, Vote::No));
+		let member = TemplateModule::members(0, 1).unwrap();
+		assert_eq!(member.highest_index_yes_vote, None);
+		assert!(TemplateModule::can_ragequit(0, &member));
+
+		advance_periods(1);
+		assert_noop!(
+			TemplateModule::submit_vote(Origin::signed(1), 0, first, Vote::Yes),
+			Error::<Test>::VotingPeriodExpired
+		);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn votes_carry_delegated_weight_until_delegators_vote() {
+	ExtBuilder::default().members(vec![(1, 1), (3, 2), (4, 4)]).build().execute_with(|| {
+		assert_ok!(TemplateModule::delegate_votes(Origin::signed(4), 0, 3));
+		assert_ok!(TemplateModule::delegate_votes(Origin::signed(3), 0, 1));
+		assert_eq!(last_dao_event(), RawEvent::VotesDelegated(0, 3, 1));
+		let index = sponsored_membership_proposal(1, 0);
+		advance_periods(1);
+
+		// Member 1 votes with the shares of 3 and, through 3, of 4. Only the two delegators
+		// visited are charged.
+		let post_info = TemplateModule::submit_vote(Origin::signed(1), 0, index, Vote::Yes).unwrap();
+		assert_eq!(post_info.actual_weight, Some(50_000 + 12 + 4 * 10 + 2 * (6 + 3 * 10)));
+		assert_eq!(TemplateModule::vote_of((0, 0), 1), Some(Ballot { vote: Vote::Yes, shares: 7 }));
+		assert_eq!(TemplateModule::delegated_vote_of((0, 0), 4), Some((1, 4)));
+
+		// Voting directly takes 3's shares and those delegated through 3 back from 1.
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(3), 0, index, Vote::No));
+		assert_eq!(TemplateModule::vote_of((0, 0), 1), Some(Ballot { vote: Vote::Yes, shares: 1 }));
+		assert_eq!(TemplateModule::vote_of((0, 0), 3), Some(Ballot { vote: Vote::No, shares: 6 }));
+		assert_eq!(TemplateModule::delegated_vote_of((0, 0), 3), None);
+		assert_eq!(TemplateModule::delegated_vote_of((0, 0), 4), Some((3, 4)));
+
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(4), 0, index, Vote::Abstain));
+		let proposal = TemplateModule::proposals(0, 0).unwrap();
+		assert_eq!((proposal.yes_votes, proposal.no_votes, proposal.abstain_votes), (1, 2, 4));
+
+		// Revoking a delegation leaves ballots cast before as they are.
+		assert_ok!(TemplateModule::undelegate_votes(Origin::signed(4), 0));
+		assert_eq!(last_dao_event(), RawEvent::DelegationRevoked(0, 4));
+		assert_eq!(TemplateModule::delegation(0, 4), None);
+		assert_eq!(TemplateModule::delegators(0, 3), Vec::<AccountId>::new());
+		assert_eq!(TemplateModule::vote_of((0, 0), 3), Some(Ballot { vote: Vote::No, shares: 2 }));
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn delegators_cannot_ragequit_while_their_shares_count_as_yes() {
+	ExtBuilder::default().members(vec![(1, 1), (3, 2)]).build().execute_with(|| {
+		assert_ok!(TemplateModule::delegate_votes(Origin::signed(3), 0, 1));
+		let index = sponsored_membership_proposal(1, 0);
+		advance_periods(1);
+
+		// The yes vote of 1 counts the shares of 3, who must stay until it is processed.
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, index, Vote::Yes));
+		assert_eq!(TemplateModule::members(0, 3).unwrap().highest_index_yes_vote, Some(index));
+		assert_noop!(TemplateModule::ragequit(Origin::signed(3), 0, 1, 0), Error::<Test>::CannotRagequitYet);
+
+		// The lock follows the ballot as it changes, and is lifted when 3 votes no themselves.
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, index, Vote::No));
+		assert_eq!(TemplateModule::members(0, 3).unwrap().highest_index_yes_vote, None);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, index, Vote::Yes));
+		assert_eq!(TemplateModule::members(0, 3).unwrap().highest_index_yes_vote, Some(index));
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(3), 0, index, Vote::No));
+		assert_eq!(TemplateModule::members(0, 3).unwrap().highest_index_yes_vote, None);
+		assert_ok!(TemplateModule::ragequit(Origin::signed(3), 0, 1, 0));
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn delegation_depth_counts_delegations_in_both_directions() {
+	ExtBuilder::default().members((1..=4).map(|who| (who, 1)).collect()).build().execute_with(|| {
+		// 1 -> 2 -> 3 -> 4 follows three delegations, whichever order it is built in. Built from
+		// its start, the last link only leads on to 4 but 1 and 2 delegate to 3 already.
+		assert_ok!(TemplateModule::delegate_votes(Origin::signed(1), 0, 2));
+		assert_ok!(TemplateModule::delegate_votes(Origin::signed(2), 0, 3));
+		assert_noop!(TemplateModule::delegate_votes(Origin::signed(3), 0, 4), Error::<Test>::DelegationTooDeep);
+		assert_ok!(TemplateModule::undelegate_votes(Origin::signed(1), 0));
+		assert_ok!(TemplateModule::undelegate_votes(Origin::signed(2), 0));
+
+		// Built from its end, the first link leads on through 2 and 3.
+		assert_ok!(TemplateModule::delegate_votes(Origin::signed(3), 0, 4));
+		assert_ok!(TemplateModule::delegate_votes(Origin::signed(2), 0, 3));
+		assert_noop!(TemplateModule::delegate_votes(Origin::signed(1), 0, 2), Error::<Test>::DelegationTooDeep);
+		assert_ok!(TemplateModule::undelegate_votes(Origin::signed(2), 0));
+
+		// Joining two halves counts both.
+		assert_ok!(TemplateModule::delegate_votes(Origin::signed(1), 0, 2));
+		assert_noop!(TemplateModule::delegate_votes(Origin::signed(2), 0, 3), Error::<Test>::DelegationTooDeep);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn delegations_are_checked() {
+	ExtBuilder::default().members((1..=5).map(|who| (who, 1)).collect()).build().execute_with(|| {
+		assert_noop!(TemplateModule::delegate_votes(Origin::signed(1), 0, 1), Error::<Test>::DelegationCycle);
+		assert_noop!(TemplateModule::delegate_votes(Origin::signed(1), 0, 9), Error::<Test>::DelegateNotMember);
+		assert_noop!(TemplateModule::delegate_votes(Origin::signed(9), 0, 1), Error::<Test>::NotDelegate);
+		assert_noop!(TemplateModule::undelegate_votes(Origin::signed(1), 0), Error::<Test>::NotDelegating);
+
+		assert_ok!(TemplateModule::delegate_votes(Origin::signed(1), 0, 2));
+		assert_ok!(TemplateModule::delegate_votes(Origin::signed(2), 0, 3));
+		assert_noop!(TemplateModule::delegate_votes(Origin::signed(3), 0, 1), Error::<Test>::DelegationCycle);
+		// 5 -> 1 -> 2 -> 3 would follow three delegations.
+		assert_noop!(TemplateModule::delegate_votes(Origin::signed(5), 0, 1), Error::<Test>::DelegationTooDeep);
+
+		assert_ok!(TemplateModule::delegate_votes(Origin::signed(4), 0, 3));
+		assert_noop!(TemplateModule::delegate_votes(Origin::signed(5), 0, 3), Error::<Test>::TooManyDelegators);
+
+		// Delegating again replaces the previous delegation.
+		assert_ok!(TemplateModule::delegate_votes(Origin::signed(4), 0, 5));
+		assert_eq!(TemplateModule::delegators(0, 3), vec![2]);
+		assert_eq!(TemplateModule::delegators(0, 5), vec![4]);
+		assert_ok!(TemplateModule::delegate_votes(Origin::signed(1), 0, 3));
+		assert_eq!(TemplateModule::delegators(0, 3), vec![2, 1]);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn quadratic_votes_weigh_square_root_of_committed_shares() {
+	new_test_ext().execute_with(|| {
+		run_to_block(3);
+		let thresholds = Thresholds {
+			membership: Threshold { approval: Perbill::from_percent(50), quorum: Perbill::from_percent(50) },
+			..Default::default()
+		};
+		assert_ok!(TemplateModule::summon(
+			Origin::signed(2),
+			DaoParams { thresholds, voting_mode: VotingMode::Quadratic, ..params() },
+			vec![(1, 16), (3, 25), (4, 9)],
+		));
+		for proposal_id in 0..2 {
+			assert_ok!(TemplateModule::submit_proposal(Origin::signed(2), 1, 2, 1, 0, 0, 0, vec![]));
+			assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 1, proposal_id));
+		}
+
+		run_to_block(5);
+		assert_noop!(
+			TemplateModule::submit_vote(Origin::signed(1), 1, 0, Vote::Yes),
+			Error::<Test>::WrongVotingMode
+		);
+		assert_noop!(
+			TemplateModule::submit_quadratic_vote(Origin::signed(1), 1, 0, Vote::Yes, 17),
+			Error::<Test>::SharesLocked
+		);
+		// The largest member is outvoted by two smaller ones.
+		assert_ok!(TemplateModule::submit_quadratic_vote(Origin::signed(3), 1, 0, Vote::No, 25));
+		assert_ok!(TemplateModule::submit_quadratic_vote(Origin::signed(1), 1, 0, Vote::Yes, 9));
+		assert_ok!(TemplateModule::submit_quadratic_vote(Origin::signed(4), 1, 0, Vote::Yes, 9));
+		assert_eq!(TemplateModule::vote_of((1, 0), 3), Some(Ballot { vote: Vote::No, shares: 5 }));
+		assert_eq!(TemplateModule::committed_shares(1, 0), 43);
+
+		run_to_block(7);
+		assert_ok!(TemplateModule::submit_quadratic_vote(Origin::signed(1), 1, 1, Vote::Yes, 7));
+		// Changing the vote replaces the commitment.
+		assert_ok!(TemplateModule::submit_quadratic_vote(Origin::signed(1), 1, 1, Vote::No, 4));
+		let proposal = TemplateModule::proposals(1, 1).unwrap();
+		assert_eq!((proposal.yes_votes, proposal.no_votes), (0, 2));
+		assert_eq!(TemplateModule::committed_shares(1, 1), 4);
+		assert_eq!(TemplateModule::locked_shares(1, &1), 13);
+		assert_noop!(
+			TemplateModule::submit_quadratic_vote(Origin::signed(3), 1, 1, Vote::No, 1),
+			Error::<Test>::SharesLocked
+		);
+		assert_noop!(TemplateModule::ragequit(Origin::signed(3), 1, 1, 0), Error::<Test>::SharesLocked);
+
+		run_to_block(11);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(2), 1, 0));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(1, 0, 0, true));
+		// Four of 51 shares committed fall short of quorum.
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(2), 1, 1));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(1, 1, 1, false));
+
+		// Processing unlocks committed shares.
+		assert_eq!(TemplateModule::locked_shares(1, &3), 0);
+		assert_ok!(TemplateModule::ragequit(Origin::signed(3), 1, 25, 0));
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn conviction_multiplies_votes_and_locks_shares() {
+	ExtBuilder::default().members(vec![(1, 1), (3, 3)]).build().execute_with(|| {
+		let index = sponsored_membership_proposal(1, 0);
+		advance_periods(1);
+
+		// One share with four times the conviction outweighs three shares.
+		let post_info =
+			TemplateModule::submit_conviction_vote(Origin::signed(1), 0, index, Vote::Yes, Conviction::Locked4x)
+				.unwrap();
+		assert_eq!(post_info.actual_weight, Some(50_000 + 12 + 4 * 10 + 2 + 3 * 10));
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(3), 0, index, Vote::No));
+		assert_eq!(TemplateModule::vote_of((0, 0), 1), Some(Ballot { vote: Vote::Yes, shares: 1 }));
+		assert_eq!(TemplateModule::conviction_tally(0, 0), (3, 0));
+		assert_noop!(
+			TemplateModule::submit_conviction_vote(Origin::signed(1), 0, index, Vote::No, Conviction::Locked2x),
+			Error::<Test>::ConvictionFixed
+		);
+
+		// Voting ends with period 4, the lock four voting periods of two later.
+		assert_eq!(TemplateModule::conviction_lock(0, 1), Some((12, 1)));
+
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(4), 0, index));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(0, index, 0, true));
+		assert_noop!(TemplateModule::ragequit(Origin::signed(1), 0, 1, 0), Error::<Test>::SharesLocked);
+
+		run_to_block(12);
+		assert_eq!(TemplateModule::conviction_locked(0, &1), 0);
+		assert_ok!(TemplateModule::ragequit(Origin::signed(1), 0, 1, 0));
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn conviction_locks_loot_of_kicked_members() {
+	ExtBuilder::default().members(vec![(1, 3), (3, 1)]).build().execute_with(|| {
+		let index = sponsored_membership_proposal(1, 0);
+		run_to_block(2);
+		assert_ok!(
+			TemplateModule::submit_conviction_vote(Origin::signed(3), 0, index, Vote::Yes, Conviction::Locked4x)
+		);
+		assert_eq!(TemplateModule::conviction_lock(0, 3), Some((12, 1)));
+
+		assert_ok!(TemplateModule::submit_guild_kick_proposal(Origin::signed(1), 0, 3, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 1));
+		run_to_block(3);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 1, Vote::Yes));
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(4), 0, index));
+		run_to_block(6);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(4), 0, 1));
+		assert!(TemplateModule::is_jailed(0, &3));
+
+		// The kicked member's shares turned into loot, which stays locked like the shares.
+		assert_noop!(TemplateModule::ragekick(Origin::signed(4), 0, 3), Error::<Test>::SharesLocked);
+
+		run_to_block(12);
+		assert_ok!(TemplateModule::ragekick(Origin::signed(4), 0, 3));
+		assert_eq!(TemplateModule::members(0, 3).map(|m| m.loot), Some(0));
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn votes_weigh_shares_held_at_sponsorship() {
+	ExtBuilder::default().members(vec![(1, 1), (3, 1)]).build().execute_with(|| {
+		let first = sponsored_membership_proposal(5, 0);
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, first, Vote::Yes));
+
+		run_to_block(4);
+		let second = sponsored_membership_proposal(1, 0);
+		// Shares count as held at the end of the block before the sponsorship.
+		assert_eq!(TemplateModule::proposal_snapshot(0, 1), Some((3, 2)));
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, first));
+		assert_eq!(TemplateModule::shares_at(0, &2, 4), 0);
+		assert_eq!(TemplateModule::shares_at(0, &2, 5), 5);
+
+		// The new member's shares arrived after the second proposal was sponsored.
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(2), 0, second, Vote::Yes));
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(3), 0, second, Vote::No));
+		assert_eq!(TemplateModule::vote_of((0, 1), 2), Some(Ballot { vote: Vote::Yes, shares: 0 }));
+
+		run_to_block(8);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, second));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(0, second, 1, false));
+		assert_eq!(TemplateModule::proposal_snapshot(0, 1), None);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn share_checkpoints_are_pruned_and_bounded() {
+	ExtBuilder::default().members(vec![(1, 1), (3, 10)]).build().execute_with(|| {
+		// Without unprocessed proposals only the checkpoint in effect at the end of the previous
+		// block is kept.
+		assert_ok!(TemplateModule::ragequit(Origin::signed(3), 0, 1, 0));
+		run_to_block(2);
+		assert_ok!(TemplateModule::ragequit(Origin::signed(3), 0, 1, 0));
+		assert_eq!(TemplateModule::share_checkpoints(0, 3), vec![(1, 9), (2, 8)]);
+
+		// A proposal sponsored in block 3 keeps the checkpoint of block 2. Past three
+		// checkpoints the oldest two are merged into the lower of their shares.
+		run_to_block(3);
+		let index = sponsored_membership_proposal(1, 0);
+		assert_ok!(TemplateModule::ragequit(Origin::signed(3), 0, 1, 0));
+		run_to_block(4);
+		assert_ok!(TemplateModule::ragequit(Origin::signed(3), 0, 1, 0));
+		run_to_block(5);
+		assert_ok!(TemplateModule::ragequit(Origin::signed(3), 0, 1, 0));
+		assert_eq!(TemplateModule::share_checkpoints(0, 3), vec![(2, 7), (4, 6), (5, 5)]);
+		assert_eq!(TemplateModule::shares_at(0, &3, 2), 7);
+
+		run_to_block(7);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(4), 0, index));
+		assert_eq!(TemplateModule::processed_queue_length(0), 1);
+		assert_ok!(TemplateModule::ragequit(Origin::signed(3), 0, 1, 0));
+		assert_eq!(TemplateModule::share_checkpoints(0, 3), vec![(5, 5), (7, 4)]);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn secret_votes_are_committed_then_revealed() {
+	new_test_ext().execute_with(|| {
+		run_to_block(3);
+		assert_ok!(TemplateModule::summon(
+			Origin::signed(2),
+			DaoParams { reveal_period_length: 1, reveal_deposit: 3, ..params() },
+			vec![(1, 1), (3, 2), (4, 1)],
+		));
+		assert_ok!(TemplateModule::submit_guild_kick_proposal(Origin::signed(2), 1, 4, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 1, 0));
+
+		run_to_block(5);
+		assert_noop!(TemplateModule::submit_vote(Origin::signed(1), 1, 0, Vote::Yes), Error::<Test>::SecretBallot);
+		for (member, vote) in [(1, Vote::Yes), (3, Vote::Yes), (4, Vote::No)].iter() {
+			let commitment = BlakeTwo256::hash_of(&(*vote, [*member as u8; 32]));
+			assert_ok!(TemplateModule::commit_vote(Origin::signed(*member), 1, 0, commitment));
+		}
+		assert_eq!(last_dao_event(), RawEvent::VoteCommitted(1, 0, 4));
+		// Member 1 holds the sponsor's deposit as well as the reveal deposit.
+		assert_eq!(Balances::reserved_balance(1), 4 + 3);
+		assert_eq!(TemplateModule::proposals(1, 0).map(|p| p.yes_votes), Some(0));
+		assert_noop!(
+			TemplateModule::reveal_vote(Origin::signed(1), 1, 0, Vote::Yes, [1; 32]),
+			Error::<Test>::NotRevealPeriod
+		);
+
+		run_to_block(7);
+		assert_noop!(
+			TemplateModule::commit_vote(Origin::signed(1), 1, 0, Default::default()),
+			Error::<Test>::VotingPeriodExpired
+		);
+		assert_noop!(
+			TemplateModule::reveal_vote(Origin::signed(1), 1, 0, Vote::No, [1; 32]),
+			Error::<Test>::InvalidReveal
+		);
+		assert_ok!(TemplateModule::reveal_vote(Origin::signed(1), 1, 0, Vote::Yes, [1; 32]));
+		assert_ok!(TemplateModule::reveal_vote(Origin::signed(3), 1, 0, Vote::Yes, [3; 32]));
+		assert_eq!(Balances::reserved_balance(1), 4);
+		assert_noop!(
+			TemplateModule::punish_unrevealed(Origin::signed(2), 1, 0, 4),
+			Error::<Test>::RevealNotOver
+		);
+
+		// Voting and revealing take two periods before the grace period.
+		run_to_block(9);
+		assert_noop!(
+			TemplateModule::process_proposal(Origin::signed(2), 1, 0),
+			Error::<Test>::ProposalNotReady
+		);
+		run_to_block(11);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(2), 1, 0));
+		// The unrevealed no vote was discarded.
+		assert_eq!(last_dao_event(), RawEvent::GuildKickProcessed(1, 0, 0, true));
+
+		let guild_balance = TemplateModule::guild_balance(1);
+		assert_ok!(TemplateModule::punish_unrevealed(Origin::signed(2), 1, 0, 4));
+		assert_eq!(last_dao_event(), RawEvent::UnrevealedVotePunished(1, 0, 4, 3));
+		assert_eq!(TemplateModule::guild_balance(1), guild_balance + 3);
+		assert_eq!(Balances::reserved_balance(4), 0);
+		assert_noop!(
+			TemplateModule::punish_unrevealed(Origin::signed(2), 1, 0, 4),
+			Error::<Test>::NoCommitment
+		);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
 
 #[test]
-fn it_works_for_default_value() {
+fn failed_proposal_returns_tribute() {
 	new_test_ext().execute_with(|| {
-		// Just a dummy test for the dummy function `do_something`
-		// calling the `do_something` function with a value 42
-		assert_ok!(TemplateModule::do_something(Origin::signed(1), 42));
-		// asserting that the stored value is equal to what we stored
-		assert_eq!(TemplateModule::something(), Some(42));
+		let index = sponsored_membership_proposal(10, 50);
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, index, Vote::No));
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, index));
+
+		assert_eq!(TemplateModule::members(0, 2), None);
+		assert_eq!(TemplateModule::user_balance(0, 2), 50);
+		assert_ok!(TemplateModule::withdraw_balance(Origin::signed(2), 0, 50));
+		assert_eq!(Balances::free_balance(2), 100);
+		assert_noop!(
+			TemplateModule::withdraw_balance(Origin::signed(2), 0, 1),
+			Error::<Test>::InsufficientBalance
+		);
+		assert_ok!(TemplateModule::do_try_state());
 	});
 }
 
 #[test]
-fn correct_error_for_none_value() {
+fn proposals_must_be_processed_in_order() {
 	new_test_ext().execute_with(|| {
-		// Ensure the correct error is thrown on None value
+		let first = sponsored_membership_proposal(1, 0);
+		let second = sponsored_membership_proposal(1, 0);
+		run_to_block(10);
+		assert_noop!(
+			TemplateModule::process_proposal(Origin::signed(3), 0, second),
+			Error::<Test>::PreviousProposalUnprocessed
+		);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, first));
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, second));
+	});
+}
+
+#[test]
+fn timelocked_payments_wait_for_ragequits_and_guardians() {
+	ExtBuilder::default().members(vec![(1, 1), (3, 1), (4, 1)]).bank(50).build().execute_with(|| {
+		<Daos<Test>>::mutate(0, |p| p.as_mut().unwrap().timelock_periods = 2);
+		assert_ok!(TemplateModule::set_guardians(RawOrigin::Dao(0).into(), vec![4, 3, 1, 3]));
+		assert_eq!(TemplateModule::guardians(0), vec![1, 3, 4]);
+		assert_noop!(TemplateModule::set_guardians(Origin::signed(1), vec![1]), DispatchError::BadOrigin);
+
+		assert_ok!(TemplateModule::submit_proposal(Origin::signed(2), 0, 2, 0, 0, 0, 30, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0));
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 0, Vote::Yes));
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 0));
+		assert!(System::events().iter().any(|r| r.event == TestEvent::template(RawEvent::Timelocked(0, 0, 7))));
+		assert_eq!(TemplateModule::user_balance(0, 2), 0);
+		assert_noop!(
+			TemplateModule::execute_timelocked(Origin::signed(2), 0, 0),
+			Error::<Test>::TimelockNotOver
+		);
+
+		// A dissenter leaves with their share of the payment before it is made.
+		assert_ok!(TemplateModule::ragequit(Origin::signed(4), 0, 1, 0));
+		assert_eq!(TemplateModule::user_balance(0, 4), 16);
+		run_to_block(7);
+		assert_ok!(TemplateModule::execute_timelocked(Origin::signed(2), 0, 0));
+		assert_eq!(last_dao_event(), RawEvent::TimelockExecuted(0, 0));
+		assert_eq!(TemplateModule::user_balance(0, 2), 30);
+		assert_eq!(TemplateModule::guild_balance(0), 4);
+
+		assert_ok!(TemplateModule::submit_proposal(Origin::signed(2), 0, 2, 0, 0, 0, 3, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 1));
+		run_to_block(8);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 1, Vote::Yes));
+		run_to_block(11);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 1));
+		assert_eq!(TemplateModule::timelock(0, 1), Some(13));
+
+		// Two of three guardians cancel the payment.
+		assert_noop!(TemplateModule::cancel_timelocked(Origin::signed(2), 0, 1), Error::<Test>::NotGuardian);
+		assert_ok!(TemplateModule::cancel_timelocked(Origin::signed(1), 0, 1));
+		assert_eq!(last_dao_event(), RawEvent::CancellationApproved(0, 1, 1));
+		assert_noop!(TemplateModule::cancel_timelocked(Origin::signed(1), 0, 1), Error::<Test>::AlreadyApproved);
+		assert_ok!(TemplateModule::cancel_timelocked(Origin::signed(4), 0, 1));
+		assert_eq!(last_dao_event(), RawEvent::TimelockCancelled(0, 1));
+		assert_eq!(TemplateModule::cancel_approvals(0, 1), Vec::<AccountId>::new());
+
+		run_to_block(13);
+		assert_noop!(
+			TemplateModule::execute_timelocked(Origin::signed(2), 0, 1),
+			Error::<Test>::NotTimelocked
+		);
+		assert_eq!(TemplateModule::guild_balance(0), 4);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn vesting_grants_are_revoked_when_the_recipient_is_kicked() {
+	ExtBuilder::default().members(vec![(1, 2), (2, 1)]).bank(100).build().execute_with(|| {
+		let schedule = VestingSchedule { cliff: 2, duration: 20 };
+		assert_noop!(
+			TemplateModule::submit_vesting_proposal(
+				Origin::signed(3), 0, 2, 0, 40, VestingSchedule { cliff: 21, duration: 20 }, vec![],
+			),
+			Error::<Test>::InvalidSchedule
+		);
+		assert_ok!(TemplateModule::submit_vesting_proposal(Origin::signed(3), 0, 2, 0, 40, schedule, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0));
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 0, Vote::Yes));
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 0));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(0, 0, 0, true));
+		assert!(System::events().iter().any(|r| r.event == TestEvent::template(RawEvent::GrantStarted(0, 0, 2, 40))));
+		assert_eq!(TemplateModule::guild_balance(0), 60);
+		assert_eq!(TemplateModule::vesting_balance(0), 40);
+		assert_eq!(TemplateModule::grants(0, 2).len(), 1);
+
+		// Nothing vests before the cliff, then 2 every block.
+		run_to_block(6);
+		assert_noop!(TemplateModule::claim_vested(Origin::signed(2), 0, 0), Error::<Test>::NothingVested);
+		run_to_block(8);
+		assert_ok!(TemplateModule::claim_vested(Origin::signed(2), 0, 0));
+		assert_eq!(last_dao_event(), RawEvent::VestedClaimed(0, 0, 2, 6));
+		assert_eq!(TemplateModule::user_balance(0, 2), 6);
+		assert_noop!(TemplateModule::claim_vested(Origin::signed(2), 0, 0), Error::<Test>::NothingVested);
+
+		// Kicking the recipient credits them what vested and returns the rest to the guild.
+		assert_ok!(TemplateModule::submit_guild_kick_proposal(Origin::signed(1), 0, 2, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 1));
+		run_to_block(9);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 1, Vote::Yes));
+		run_to_block(12);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 1));
+		assert!(System::events().iter().any(|r| r.event == TestEvent::template(RawEvent::GrantRevoked(0, 0, 2, 26))));
+		assert_eq!(TemplateModule::user_balance(0, 2), 14);
+		assert_eq!(TemplateModule::guild_balance(0), 86);
+		assert_eq!(TemplateModule::vesting_balance(0), 0);
+		assert_eq!(TemplateModule::grants(0, 2), vec![]);
+		assert_noop!(TemplateModule::claim_vested(Origin::signed(2), 0, 0), Error::<Test>::UnknownGrant);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn dao_can_revoke_vesting_grants() {
+	ExtBuilder::default().members(vec![(1, 2), (2, 1)]).bank(100).build().execute_with(|| {
+		let schedule = VestingSchedule { cliff: 2, duration: 20 };
+		assert_ok!(TemplateModule::submit_vesting_proposal(Origin::signed(3), 0, 2, 0, 40, schedule, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0));
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 0, Vote::Yes));
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 0));
+
+		// The recipient is credited what vested and the rest returns to the guild.
+		run_to_block(8);
+		assert_noop!(TemplateModule::revoke_grant(Origin::signed(1), 2, 0), DispatchError::BadOrigin);
+		assert_noop!(TemplateModule::revoke_grant(RawOrigin::Dao(0).into(), 1, 0), Error::<Test>::UnknownGrant);
+		assert_ok!(TemplateModule::revoke_grant(RawOrigin::Dao(0).into(), 2, 0));
+		assert_eq!(last_dao_event(), RawEvent::GrantRevoked(0, 0, 2, 34));
+		assert_eq!(TemplateModule::user_balance(0, 2), 6);
+		assert_eq!(TemplateModule::guild_balance(0), 94);
+		assert_eq!(TemplateModule::vesting_balance(0), 0);
+		assert_eq!(TemplateModule::grants(0, 2), vec![]);
+		assert!(!TemplateModule::is_jailed(0, &2));
+		assert_noop!(TemplateModule::claim_vested(Origin::signed(2), 0, 0), Error::<Test>::UnknownGrant);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn milestone_grants_pay_tranches_as_members_approve_them() {
+	ExtBuilder::default().members(vec![(1, 2), (3, 1), (4, 1)]).bank(100).build().execute_with(|| {
+		let milestones: Vec<_> = [10, 20, 30].iter()
+			.map(|&amount| Milestone { amount, deliverable: BlakeTwo256::hash_of(&amount) })
+			.collect();
 		assert_noop!(
-			TemplateModule::cause_error(Origin::signed(1)),
-			Error::<Test>::NoneValue
+			TemplateModule::submit_milestone_proposal(Origin::signed(2), 0, 2, 0, vec![], vec![]),
+			Error::<Test>::InvalidMilestones
 		);
+		assert_ok!(TemplateModule::submit_milestone_proposal(Origin::signed(2), 0, 2, 0, milestones, vec![]));
+		assert_eq!(TemplateModule::proposals(0, 0).unwrap().payment_requested, 60);
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0));
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 0, Vote::Yes));
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 0));
+		assert!(System::events().iter().any(|r| r.event == TestEvent::template(RawEvent::TranchePaid(0, 0, 0, 10))));
+		assert_eq!(TemplateModule::user_balance(0, 2), 10);
+		assert_eq!(TemplateModule::milestone_balance(0), 50);
+		assert_eq!(TemplateModule::guild_balance(0), 40);
+
+		// The next tranche is paid once more than half of the shares approve its milestone.
+		assert_ok!(TemplateModule::approve_milestone(Origin::signed(1), 0, 0));
+		assert_eq!(last_dao_event(), RawEvent::MilestoneApprovalSupported(0, 0, 1));
+		assert_noop!(TemplateModule::approve_milestone(Origin::signed(1), 0, 0), Error::<Test>::AlreadyVoted);
+		assert_ok!(TemplateModule::approve_milestone(Origin::signed(3), 0, 0));
+		assert_eq!(last_dao_event(), RawEvent::TranchePaid(0, 0, 1, 20));
+		assert_eq!(TemplateModule::user_balance(0, 2), 30);
+		assert_eq!(TemplateModule::milestone_grant(0, 0).unwrap().next, 2);
+		assert_eq!(TemplateModule::milestone_support((0, 0), 1), 0);
+
+		// Cancelling the grant releases the unpaid tranche back to the guild.
+		assert_noop!(TemplateModule::cancel_milestone_grant(Origin::signed(1), 0), DispatchError::BadOrigin);
+		assert_ok!(TemplateModule::cancel_milestone_grant(RawOrigin::Dao(0).into(), 0));
+		assert_eq!(last_dao_event(), RawEvent::MilestoneGrantCancelled(0, 0, 30));
+		assert_eq!(TemplateModule::guild_balance(0), 70);
+		assert_eq!(TemplateModule::milestone_balance(0), 0);
+		assert_noop!(
+			TemplateModule::approve_milestone(Origin::signed(3), 0, 0),
+			Error::<Test>::UnknownMilestoneGrant
+		);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn milestone_support_is_dropped_with_the_supporters_shares() {
+	ExtBuilder::default().members(vec![(1, 2), (3, 1), (4, 1)]).bank(100).build().execute_with(|| {
+		let milestones: Vec<_> = [10, 20].iter()
+			.map(|&amount| Milestone { amount, deliverable: BlakeTwo256::hash_of(&amount) })
+			.collect();
+		assert_ok!(TemplateModule::submit_milestone_proposal(Origin::signed(2), 0, 2, 0, milestones, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0));
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 0, Vote::Yes));
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 0));
+
+		// Support follows the supporter's shares down as they ragequit.
+		assert_ok!(TemplateModule::approve_milestone(Origin::signed(1), 0, 0));
+		assert_eq!(TemplateModule::milestones_supported(0, 1), vec![0]);
+		assert_ok!(TemplateModule::ragequit(Origin::signed(1), 0, 1, 0));
+		assert_eq!(TemplateModule::milestone_support((0, 0), 1), 1);
+		assert_eq!(TemplateModule::milestone_grant(0, 0).unwrap().support, 1);
+		assert_ok!(TemplateModule::ragequit(Origin::signed(1), 0, 1, 0));
+		assert_eq!(TemplateModule::milestone_support((0, 0), 1), 0);
+		assert_eq!(TemplateModule::milestones_supported(0, 1), vec![]);
+		assert_eq!(TemplateModule::milestone_grant(0, 0).unwrap().support, 0);
+
+		// Members may withdraw their support.
+		assert_ok!(TemplateModule::approve_milestone(Origin::signed(3), 0, 0));
+		assert_eq!(last_dao_event(), RawEvent::MilestoneApprovalSupported(0, 0, 3));
+		assert_ok!(TemplateModule::revoke_milestone_approval(Origin::signed(3), 0, 0));
+		assert_eq!(last_dao_event(), RawEvent::MilestoneApprovalRevoked(0, 0, 3));
+		assert_eq!(TemplateModule::milestone_grant(0, 0).unwrap().support, 0);
+		assert_noop!(
+			TemplateModule::revoke_milestone_approval(Origin::signed(3), 0, 0),
+			Error::<Test>::NoMilestoneSupport
+		);
+
+		// Support stays weighed against the total shares when the vote started: both shares left
+		// are not a majority of four, although member 1 who left held the other two.
+		assert_ok!(TemplateModule::approve_milestone(Origin::signed(4), 0, 0));
+		assert_ok!(TemplateModule::approve_milestone(Origin::signed(3), 0, 0));
+		assert_eq!(TemplateModule::milestone_snapshot(0, 0), Some((4, 4)));
+		assert_eq!(TemplateModule::milestone_grant(0, 0).unwrap().support, 2);
+
+		// The vote ends after a voting period, and a new one is weighed against the two shares.
+		run_to_block(7);
+		assert_ok!(TemplateModule::approve_milestone(Origin::signed(4), 0, 0));
+		assert_eq!(TemplateModule::milestone_snapshot(0, 0), Some((6, 2)));
+		assert_eq!(TemplateModule::milestone_grant(0, 0).unwrap().support, 1);
+		assert_ok!(TemplateModule::approve_milestone(Origin::signed(3), 0, 0));
+		assert_eq!(last_dao_event(), RawEvent::TranchePaid(0, 0, 1, 20));
+		assert_eq!(TemplateModule::milestone_snapshot(0, 0), None);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn milestone_grants_to_members_kicked_in_the_timelock_are_revoked() {
+	ExtBuilder::default().members(vec![(1, 3), (2, 1)]).bank(100).build().execute_with(|| {
+		<Daos<Test>>::mutate(0, |p| p.as_mut().unwrap().timelock_periods = 2);
+		let milestones: Vec<_> = [10, 20].iter()
+			.map(|&amount| Milestone { amount, deliverable: BlakeTwo256::hash_of(&amount) })
+			.collect();
+		assert_ok!(TemplateModule::submit_milestone_proposal(Origin::signed(2), 0, 2, 0, milestones, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0));
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 0, Vote::Yes));
+		assert_ok!(TemplateModule::submit_guild_kick_proposal(Origin::signed(1), 0, 2, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 1));
+		run_to_block(3);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 1, Vote::Yes));
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 0));
+		assert_eq!(TemplateModule::timelock(0, 0), Some(7));
+		run_to_block(6);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 1));
+		assert!(TemplateModule::is_jailed(0, &2));
+
+		// Like a vesting grant, the grant is revoked without paying its first tranche.
+		run_to_block(7);
+		assert_ok!(TemplateModule::execute_timelocked(Origin::signed(3), 0, 0));
+		assert!(System::events().iter().any(|r| r.event == TestEvent::template(RawEvent::GrantRevoked(0, 0, 2, 30))));
+		assert_eq!(TemplateModule::user_balance(0, 2), 0);
+		assert_eq!(TemplateModule::guild_balance(0), 100);
+		assert_eq!(TemplateModule::milestone_balance(0), 0);
+		assert_eq!(TemplateModule::milestone_grant(0, 0), None);
+		assert_eq!(TemplateModule::proposal_milestones(0, 0), vec![]);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn guardian_vetoes_need_ratification() {
+	ExtBuilder::default().members(vec![(1, 2), (3, 2), (4, 3)]).build().execute_with(|| {
+		assert_ok!(TemplateModule::set_guardians(RawOrigin::Dao(0).into(), vec![1, 3]));
+		assert_ok!(TemplateModule::submit_proposal(Origin::signed(2), 0, 2, 10, 0, 0, 0, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(4), 0, 0));
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(4), 0, 0, Vote::Yes));
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 0, Vote::No));
+
+		run_to_block(3);
+		assert_noop!(TemplateModule::veto_proposal(Origin::signed(1), 0, 0), Error::<Test>::NotGracePeriod);
+		run_to_block(4);
+		assert_noop!(TemplateModule::veto_proposal(Origin::signed(2), 0, 0), Error::<Test>::NotGuardian);
+		assert_noop!(
+			TemplateModule::vote_on_veto(Origin::signed(1), 0, 0, true),
+			Error::<Test>::NotVetoed
+		);
+		assert_ok!(TemplateModule::veto_proposal(Origin::signed(1), 0, 0));
+		assert_eq!(last_dao_event(), RawEvent::VetoApproved(0, 0, 1));
+		assert_ok!(TemplateModule::veto_proposal(Origin::signed(3), 0, 0));
+		assert_eq!(last_dao_event(), RawEvent::Vetoed(0, 0, 6));
+
+		// Members who did not vote on the proposal can still ratify the veto.
+		assert_ok!(TemplateModule::vote_on_veto(Origin::signed(1), 0, 0, true));
+		assert_ok!(TemplateModule::vote_on_veto(Origin::signed(3), 0, 0, true));
+		assert_ok!(TemplateModule::vote_on_veto(Origin::signed(4), 0, 0, false));
+		assert_noop!(
+			TemplateModule::vote_on_veto(Origin::signed(4), 0, 0, true),
+			Error::<Test>::AlreadyVoted
+		);
+		assert_eq!(TemplateModule::veto(0, 0), Some(Veto { ratification_ends: 6, ratify: 4, overturn: 3 }));
+
+		run_to_block(5);
+		assert_noop!(
+			TemplateModule::process_proposal(Origin::signed(3), 0, 0),
+			Error::<Test>::ProposalNotReady
+		);
+		run_to_block(6);
+		assert_noop!(
+			TemplateModule::vote_on_veto(Origin::signed(1), 0, 0, false),
+			Error::<Test>::RatificationOver
+		);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 0));
+		assert!(System::events().iter().any(|r| r.event == TestEvent::template(RawEvent::VetoDecided(0, 0, true))));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(0, 0, 0, false));
+		assert_eq!(TemplateModule::members(0, 2), None);
+		assert_eq!(TemplateModule::veto(0, 0), None);
+		assert_eq!(TemplateModule::ratification_vote_of((0, 0), 1), None);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn guardians_cannot_veto_failing_proposals() {
+	ExtBuilder::default().members(vec![(1, 2), (3, 2), (4, 3)]).build().execute_with(|| {
+		assert_ok!(TemplateModule::set_guardians(RawOrigin::Dao(0).into(), vec![1, 3]));
+		assert_ok!(TemplateModule::submit_proposal(Origin::signed(2), 0, 2, 10, 0, 0, 0, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(4), 0, 0));
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(4), 0, 0, Vote::No));
+
+		run_to_block(4);
+		assert_noop!(TemplateModule::veto_proposal(Origin::signed(1), 0, 0), Error::<Test>::NotPassing);
+		assert_eq!(TemplateModule::veto_approvals(0, 0), Vec::<AccountId>::new());
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 0));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(0, 0, 0, false));
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn paused_dao_only_lets_members_leave() {
+	ExtBuilder::default().members(vec![(1, 1), (3, 1), (4, 2)]).bank(30).build().execute_with(|| {
+		assert_ok!(TemplateModule::set_guardians(RawOrigin::Dao(0).into(), vec![1, 3]));
+		assert_ok!(TemplateModule::submit_proposal(Origin::signed(2), 0, 2, 1, 0, 0, 0, vec![]));
+		assert_noop!(TemplateModule::pause(Origin::signed(2), 0), Error::<Test>::NotGuardian);
+		assert_ok!(TemplateModule::pause(Origin::signed(1), 0));
+		assert_eq!(last_dao_event(), RawEvent::Paused(0));
+
+		assert_noop!(
+			TemplateModule::submit_proposal(Origin::signed(2), 0, 2, 1, 0, 0, 0, vec![]),
+			Error::<Test>::DaoPaused
+		);
+		assert_noop!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0), Error::<Test>::DaoPaused);
+		// Paused calls do not even enter the transaction pool.
+		let info = DispatchInfo::default();
+		let ext = ValidateDaoCall::<Test>::new();
+		let sponsor = Call::TemplateModule(crate::Call::sponsor_proposal(0, 0));
+		assert_eq!(ext.validate(&1, &sponsor, &info, 0), invalid(Error::DaoPaused));
+		assert!(ext.clone().pre_dispatch(&1, &sponsor, &info, 0).is_err());
+		let ragequit = Call::TemplateModule(crate::Call::ragequit(0, 1, 0));
+		assert_eq!(ext.validate(&3, &ragequit, &info, 0), Ok(Default::default()));
+
+		// Members can still leave with their fair share.
+		assert_ok!(TemplateModule::ragequit(Origin::signed(3), 0, 1, 0));
+		assert_ok!(TemplateModule::withdraw_balance(Origin::signed(3), 0, 7));
+
+		assert_ok!(TemplateModule::unpause(Origin::signed(1), 0));
+		assert_eq!(last_dao_event(), RawEvent::UnpauseApproved(0, 1));
+		assert_noop!(TemplateModule::unpause(Origin::signed(1), 0), Error::<Test>::AlreadyApproved);
+		// Member 4 holds two of the three shares left and unpauses on their own.
+		assert_ok!(TemplateModule::vote_pause_toggle(Origin::signed(4), 0));
+		assert_eq!(last_dao_event(), RawEvent::Unpaused(0));
+		assert!(!TemplateModule::paused(0));
+		assert_eq!(TemplateModule::unpause_approvals(0), Vec::<AccountId>::new());
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0));
+
+		assert_ok!(TemplateModule::vote_pause_toggle(Origin::signed(1), 0));
+		assert_eq!(last_dao_event(), RawEvent::PauseToggleSupported(0, 1));
+		assert_eq!(TemplateModule::pause_support_tally(0), 1);
+		assert_noop!(TemplateModule::vote_pause_toggle(Origin::signed(1), 0), Error::<Test>::AlreadyVoted);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn paused_dao_lets_yes_voters_leave() {
+	ExtBuilder::default().members(vec![(1, 1), (3, 2), (4, 3)]).build().execute_with(|| {
+		assert_ok!(TemplateModule::set_guardians(RawOrigin::Dao(0).into(), vec![1]));
+		let index = sponsored_membership_proposal(1, 0);
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(3), 0, index, Vote::Yes));
+		assert_noop!(TemplateModule::ragequit(Origin::signed(3), 0, 2, 0), Error::<Test>::CannotRagequitYet);
+
+		// A single guardian's pause does not free yes voters before the proposal is due.
+		assert_ok!(TemplateModule::pause(Origin::signed(1), 0));
+		assert_noop!(TemplateModule::ragequit(Origin::signed(3), 0, 2, 0), Error::<Test>::CannotRagequitYet);
+
+		// Once it is due, the pause holds back its processing, so its yes voters may leave.
+		run_to_block(5);
+		assert_ok!(TemplateModule::vote_pause_toggle(Origin::signed(3), 0));
+		assert_eq!(TemplateModule::pause_snapshot(0), Some((4, 6)));
+		assert_ok!(TemplateModule::ragequit(Origin::signed(3), 0, 2, 0));
+		assert_eq!(TemplateModule::total_shares(0), 4);
+
+		// Support stays weighed against the total shares when the vote started: three of six
+		// shares are not a majority, although the member who left held two of them.
+		assert_ok!(TemplateModule::vote_pause_toggle(Origin::signed(1), 0));
+		assert!(TemplateModule::paused(0));
+		assert_eq!(TemplateModule::pause_support_tally(0), 3);
+		assert_ok!(TemplateModule::vote_pause_toggle(Origin::signed(4), 0));
+		assert_eq!(last_dao_event(), RawEvent::Unpaused(0));
+		assert_eq!(TemplateModule::pause_snapshot(0), None);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn pause_support_is_dropped_with_the_supporters_shares() {
+	ExtBuilder::default().members(vec![(1, 3), (3, 2), (4, 1)]).build().execute_with(|| {
+		assert_ok!(TemplateModule::submit_guild_kick_proposal(Origin::signed(1), 0, 4, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0));
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 0, Vote::Yes));
+		assert_ok!(TemplateModule::vote_pause_toggle(Origin::signed(3), 0));
+		assert_ok!(TemplateModule::vote_pause_toggle(Origin::signed(4), 0));
+		assert_eq!(TemplateModule::pause_support_tally(0), 3);
+
+		// Support follows the supporter's shares down as they ragequit.
+		assert_ok!(TemplateModule::ragequit(Origin::signed(3), 0, 1, 0));
+		assert_eq!(TemplateModule::pause_support(0, 3), 1);
+		assert_eq!(TemplateModule::pause_support_tally(0), 2);
+
+		// Kicked members support nothing.
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 0));
+		assert!(TemplateModule::is_jailed(0, &4));
+		assert_eq!(TemplateModule::pause_support(0, 4), 0);
+		assert_eq!(TemplateModule::pause_support_tally(0), 1);
+		assert!(!TemplateModule::paused(0));
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn pause_vote_ends_after_a_voting_period() {
+	ExtBuilder::default().members(vec![(1, 1), (3, 2), (4, 3)]).build().execute_with(|| {
+		run_to_block(2);
+		assert_ok!(TemplateModule::vote_pause_toggle(Origin::signed(3), 0));
+		run_to_block(3);
+		assert_ok!(TemplateModule::vote_pause_toggle(Origin::signed(1), 0));
+		assert_eq!(TemplateModule::pause_support_tally(0), 3);
+
+		// The vote started in block 2 and lasted two periods, so member 4 starts a new one.
+		run_to_block(4);
+		assert_ok!(TemplateModule::vote_pause_toggle(Origin::signed(4), 0));
+		assert!(!TemplateModule::paused(0));
+		assert_eq!(TemplateModule::pause_snapshot(0), Some((3, 6)));
+		assert_eq!(TemplateModule::pause_support_tally(0), 3);
+		assert_eq!(TemplateModule::pause_support(0, 3), 0);
+
+		assert_ok!(TemplateModule::vote_pause_toggle(Origin::signed(3), 0));
+		assert_eq!(last_dao_event(), RawEvent::Paused(0));
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn ragequit_pays_fair_share_of_guild_bank() {
+	new_test_ext().execute_with(|| {
+		let index = sponsored_membership_proposal(10, 50);
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, index, Vote::Yes));
+
+		// Member 1 voted yes, so cannot leave before the proposal is processed.
+		assert_noop!(
+			TemplateModule::ragequit(Origin::signed(1), 0, 1, 0),
+			Error::<Test>::CannotRagequitYet
+		);
+
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, index));
+		assert_ok!(TemplateModule::ragequit(Origin::signed(2), 0, 10, 0));
+
+		// 50 * 10 / 11, rounded down.
+		assert_eq!(TemplateModule::user_balance(0, 2), 45);
+		assert_eq!(TemplateModule::guild_balance(0), 5);
+		assert_eq!(TemplateModule::total_shares(0), 1);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn ragequit_from_funded_guild_bank() {
+	ExtBuilder::default()
+		.members(vec![(1, 3), (2, 1)])
+		.bank(100)
+		.build()
+		.execute_with(|| {
+			assert_ok!(TemplateModule::ragequit(Origin::signed(2), 0, 1, 0));
+			assert_eq!(last_event(), TestEvent::template(RawEvent::Ragequit(0, 2, 1, 0)));
+			assert_eq!(TemplateModule::user_balance(0, 2), 25);
+
+			assert_ok!(TemplateModule::withdraw_balance(Origin::signed(2), 0, 25));
+			assert_eq!(Balances::free_balance(2), 125);
+			assert_eq!(Balances::free_balance(TemplateModule::account_id(0)), 76);
+			assert_ok!(TemplateModule::do_try_state());
+		});
+}
+
+#[test]
+fn guild_kick_converts_shares_to_loot() {
+	new_test_ext().execute_with(|| {
+		let index = sponsored_membership_proposal(10, 50);
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, index, Vote::Yes));
+		run_to_block(5);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, index));
+
+		assert_ok!(TemplateModule::submit_guild_kick_proposal(Origin::signed(1), 0, 2, vec![]));
+		assert_noop!(
+			TemplateModule::submit_guild_kick_proposal(Origin::signed(1), 0, 2, vec![]),
+			Error::<Test>::AlreadyProposedToKick
+		);
+		// Sponsored in the next block, so that the new member's shares count.
+		run_to_block(6);
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 1));
+		run_to_block(7);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 1, Vote::Yes));
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(2), 0, 1, Vote::No));
+
+		// 1 share for, 10 against.
+		run_to_block(10);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 1));
+		assert!(!TemplateModule::is_jailed(0, &2));
+
+		assert_ok!(TemplateModule::submit_guild_kick_proposal(Origin::signed(3), 0, 2, vec![]));
+		assert_ok!(TemplateModule::ragequit(Origin::signed(2), 0, 5, 0));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 2));
+		run_to_block(11);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 2, Vote::Yes));
+		run_to_block(14);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 2));
+
+		let kicked = TemplateModule::members(0, 2).unwrap();
+		assert!(kicked.jailed);
+		assert_eq!((kicked.shares, kicked.loot), (0, 5));
+		assert_eq!(TemplateModule::total_shares(0), 1);
+		assert_eq!(TemplateModule::total_loot(0), 5);
+		assert_noop!(
+			TemplateModule::submit_proposal(Origin::signed(3), 0, 2, 1, 0, 0, 0, vec![]),
+			Error::<Test>::ApplicantJailed
+		);
+
+		assert_ok!(TemplateModule::ragekick(Origin::signed(3), 0, 2));
+		assert_eq!(TemplateModule::members(0, 2).map(|m| m.loot), Some(0));
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn delegate_key_acts_for_member() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::update_delegate_key(Origin::signed(1), 0, 4));
+		assert_ok!(TemplateModule::submit_proposal(Origin::signed(2), 0, 2, 1, 0, 0, 0, vec![]));
+		assert_noop!(
+			TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0),
+			Error::<Test>::NotDelegate
+		);
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(4), 0, 0));
+		assert_eq!(Balances::reserved_balance(4), 10);
+
+		run_to_block(2);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(4), 0, 0, Vote::Yes));
+		assert_eq!(TemplateModule::vote_of((0, 0), 1), Some(Ballot { vote: Vote::Yes, shares: 1 }));
+	});
+}
+
+fn vote_call(proposal_index: ProposalIndex) -> Call {
+	Call::TemplateModule(crate::Call::submit_vote(0, proposal_index, Vote::Yes))
+}
+
+fn invalid(error: Error<Test>) -> TransactionValidity {
+	Err(InvalidTransaction::Custom(error.as_u8()).into())
+}
+
+#[test]
+fn only_votes_from_members_are_valid() {
+	new_test_ext().execute_with(|| {
+		let info = DispatchInfo::default();
+		let ext = ValidateDaoCall::<Test>::new();
+		// Votes pay their fee up front and are refunded after dispatch.
+		assert_eq!(vote_call(0).get_dispatch_info().pays_fee, Pays::Yes);
+		assert_eq!(ext.validate(&1, &vote_call(0), &info, 0), invalid(Error::UnknownProposalIndex));
+
+		let index = sponsored_membership_proposal(1, 0);
+		assert_eq!(ext.validate(&1, &vote_call(index), &info, 0), invalid(Error::VotingNotStarted));
+
+		run_to_block(2);
+		assert_eq!(ext.validate(&2, &vote_call(index), &info, 0), invalid(Error::NotDelegate));
+		assert!(ext.validate(&1, &vote_call(index), &info, 0).is_ok());
+
+		// Calls that pay fees are left alone.
+		let withdraw = Call::TemplateModule(crate::Call::withdraw_balance(0, 0));
+		assert_eq!(ext.validate(&2, &withdraw, &info, 0), Ok(Default::default()));
+
+		assert_ok!(ext.clone().pre_dispatch(&1, &vote_call(index), &info, 0));
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, index, Vote::Yes));
+		assert_eq!(ext.validate(&1, &vote_call(index), &info, 0), invalid(Error::AlreadyVoted));
+	});
+}
+
+#[test]
+fn votes_are_rate_limited_per_period() {
+	new_test_ext().execute_with(|| {
+		let info = DispatchInfo::default();
+		let ext = ValidateDaoCall::<Test>::new();
+		let first = sponsored_membership_proposal(1, 0);
+		let second = sponsored_membership_proposal(1, 0);
+
+		// Both proposals are open for voting in period 3. The pool takes a single vote of the
+		// member, numbered 0, and keeps votes beyond the limit out even before it is included.
+		run_to_block(3);
+		let valid = ext.validate(&1, &vote_call(first), &info, 0).unwrap();
+		assert!(valid.requires.is_empty());
+		let period = TemplateModule::current_period(0);
+		assert!(valid.provides.contains(&(b"dao/votes", 0u32, &1u64, period, 0u32).encode()));
+		let next = ValidateDaoCall::<Test>::from(1);
+		assert_eq!(next.validate(&1, &vote_call(second), &info, 0), invalid(Error::TooManyVotes));
+		assert_eq!(next.clone().pre_dispatch(&1, &vote_call(second), &info, 0), invalid(Error::TooManyVotes));
+
+		assert_ok!(ext.clone().pre_dispatch(&1, &vote_call(first), &info, 0));
+		let post_info = TemplateModule::submit_vote(Origin::signed(1), 0, first, Vote::Yes).unwrap();
+		assert_eq!(post_info.pays_fee, Pays::No);
+		assert_eq!(TemplateModule::votes_in_period(0, &1), 1);
+		assert_eq!(ext.validate(&1, &vote_call(second), &info, 0), Err(InvalidTransaction::Stale.into()));
+
+		// The pallet counts votes itself, so votes beyond the limit that reach it pay.
+		let post_info = TemplateModule::submit_vote(Origin::signed(1), 0, first, Vote::No).unwrap();
+		assert_eq!(post_info.pays_fee, Pays::Yes);
+		assert_eq!(TemplateModule::votes_in_period(0, &1), 2);
+
+		run_to_block(4);
+		assert_eq!(TemplateModule::votes_in_period(0, &1), 0);
+		assert_ok!(ext.pre_dispatch(&1, &vote_call(second), &info, 0));
+	});
+}
+
+/// Submit and sponsor an action proposal logging `i` with `weight` and return its queue index.
+fn sponsored_action_proposal(i: u32, weight: u64) -> u64 {
+	let proposal_id = TemplateModule::proposal_count(0);
+	let action = Box::new(Call::Logger(logger::Call::log(i, weight)));
+	assert_ok!(TemplateModule::submit_action_proposal(Origin::signed(2), 0, action, vec![]));
+	assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, proposal_id));
+	TemplateModule::proposal_queue_length(0) - 1
+}
+
+#[test]
+fn passed_action_proposal_dispatches_as_dao() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Logger::log(Origin::signed(1), 42, 0), DispatchError::BadOrigin);
+
+		let index = sponsored_action_proposal(42, 5);
+		advance_periods(1);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, index, Vote::Yes));
+		advance_periods(3);
+
+		// Only the action's actual weight is charged on top of processing.
+		let post_info = TemplateModule::process_proposal(Origin::signed(3), 0, index).unwrap();
+		assert_eq!(post_info.actual_weight, Some(100_000 + 33 + 31 * 10 + 5));
+		assert_eq!(logger::log(), vec![(0, 42)]);
+		assert!(System::events().iter().any(|r| {
+			r.event == TestEvent::template(RawEvent::ActionExecuted(0, 0, Ok(())))
+		}));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(0, index, 0, true));
+
+		// Action proposals do not make the proposer a member.
+		assert_eq!(TemplateModule::members(0, 2), None);
+		assert_eq!(TemplateModule::proposal_action(0, 0), None);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn failed_action_proposal_is_not_dispatched() {
+	new_test_ext().execute_with(|| {
+		let index = sponsored_action_proposal(42, 5);
+		advance_periods(1);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, index, Vote::No));
+		advance_periods(3);
+
+		let post_info = TemplateModule::process_proposal(Origin::signed(3), 0, index).unwrap();
+		assert_eq!(post_info.actual_weight, Some(100_000 + 33 + 31 * 10));
+		assert_eq!(logger::log(), vec![]);
+		assert_eq!(TemplateModule::proposal_action(0, 0), None);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn action_proposals_are_bounded_by_max_weight() {
+	new_test_ext().execute_with(|| {
+		let action = Box::new(Call::Logger(logger::Call::log(1, MaxActionWeight::get() + 1)));
+		assert_noop!(
+			TemplateModule::submit_action_proposal(Origin::signed(2), 0, action, vec![]),
+			Error::<Test>::ActionTooHeavy
+		);
+
+		let action = Box::new(Call::Logger(logger::Call::log(1, MaxActionWeight::get())));
+		assert_ok!(TemplateModule::submit_action_proposal(Origin::signed(2), 0, action, vec![]));
+		assert_ok!(TemplateModule::cancel_proposal(Origin::signed(2), 0, 0));
+		assert_eq!(TemplateModule::proposal_action(0, 0), None);
+	});
+}
+
+#[test]
+fn passed_params_proposal_applies_to_later_proposals() {
+	new_test_ext().execute_with(|| {
+		let old_params = TemplateModule::dao(0).unwrap();
+		let new_params = DaoParams { proposal_deposit: 4, processing_reward: 2, ..old_params.clone() };
+		assert_noop!(
+			TemplateModule::submit_params_proposal(
+				Origin::signed(2),
+				0,
+				DaoParams { period_duration: 2, ..new_params.clone() },
+				vec![],
+			),
+			Error::<Test>::InvalidParams
+		);
+
+		assert_ok!(TemplateModule::submit_params_proposal(Origin::signed(2), 0, new_params.clone(), vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0));
+		// Submitted before the change, sponsored after it.
+		assert_ok!(TemplateModule::submit_proposal(Origin::signed(2), 0, 2, 1, 0, 0, 0, vec![]));
+
+		advance_periods(1);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 0, Vote::Yes));
+		advance_periods(3);
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 0));
+		assert!(System::events().iter().any(|r| {
+			r.event == TestEvent::template(RawEvent::ParamsChanged(0, 0))
+		}));
+		assert_eq!(TemplateModule::dao(0), Some(new_params.clone()));
+		assert_eq!(TemplateModule::param_change(0, 0), None);
+		assert_eq!(TemplateModule::proposal_params(0, 0), None);
+		assert_eq!(Balances::reserved_balance(1), 0);
+
+		// Proposals keep the parameters they were submitted with.
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 1));
+		assert_eq!(Balances::reserved_balance(1), 10);
+		assert_eq!(TemplateModule::proposal_params(0, 1), Some(old_params));
+		sponsored_membership_proposal(1, 0);
+		assert_eq!(Balances::reserved_balance(1), 14);
+		assert_eq!(TemplateModule::proposal_params(0, 2), Some(new_params));
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn failed_params_proposal_keeps_params() {
+	new_test_ext().execute_with(|| {
+		let params = TemplateModule::dao(0).unwrap();
+		let new_params = DaoParams { voting_period_length: 5, ..params.clone() };
+		assert_ok!(TemplateModule::submit_params_proposal(Origin::signed(2), 0, new_params, vec![]));
+		assert_ok!(TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0));
+		advance_periods(1);
+		assert_ok!(TemplateModule::submit_vote(Origin::signed(1), 0, 0, Vote::No));
+		advance_periods(3);
+
+		assert_ok!(TemplateModule::process_proposal(Origin::signed(3), 0, 0));
+		assert_eq!(last_dao_event(), RawEvent::ProposalProcessed(0, 0, 0, false));
+		assert_eq!(TemplateModule::dao(0), Some(params));
+		assert_eq!(TemplateModule::param_change(0, 0), None);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn cancelled_proposal_refunds_tribute() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::submit_proposal(Origin::signed(2), 0, 2, 1, 0, 30, 0, vec![]));
+		assert_noop!(
+			TemplateModule::cancel_proposal(Origin::signed(3), 0, 0),
+			Error::<Test>::NotProposer
+		);
+		assert_ok!(TemplateModule::cancel_proposal(Origin::signed(2), 0, 0));
+		assert_eq!(TemplateModule::user_balance(0, 2), 30);
+		assert_noop!(
+			TemplateModule::sponsor_proposal(Origin::signed(1), 0, 0),
+			Error::<Test>::ProposalCancelled
+		);
+	});
+}
+
+#[test]
+fn imbalances_are_credited_to_guild() {
+	new_test_ext().execute_with(|| {
+		let fee = Balances::withdraw(
+			&2,
+			10,
+			WithdrawReason::TransactionPayment.into(),
+			ExistenceRequirement::KeepAlive,
+		).unwrap();
+		TemplateModule::on_unbalanced(fee);
+
+		assert_eq!(TemplateModule::guild_balance(0), 10);
+		assert_eq!(Balances::free_balance(TemplateModule::account_id(0)), 11);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn migrates_template_storage() {
+	// The node template's layout: no DAO storage, only the dummy `Something` value.
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 10)] }.assimilate_storage(&mut t).unwrap();
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| {
+		System::set_block_number(7);
+		put_storage_value(b"TemplateModule", b"Something", &[], 42u32);
+		assert_eq!(StorageVersion::get(), Releases::V0);
+
+		// One read of the version, then the upgrade summoning both summoners.
+		assert_eq!(TemplateModule::on_runtime_upgrade(), 1 + (23 + 17 * 10));
+		assert_eq!(get_storage_value::<u32>(b"TemplateModule", b"Something", &[]), None);
+		assert_eq!(StorageVersion::get(), Releases::V1);
+		assert_eq!(TemplateModule::summoning_time(0), 7);
+		assert_eq!(TemplateModule::members(0, 1), Some(Member { delegate_key: 1, shares: 3, ..Default::default() }));
+		assert_eq!(TemplateModule::member_by_delegate_key(0, 2), Some(2));
+		assert_eq!(TemplateModule::total_shares(0), 5);
+		assert_eq!(TemplateModule::share_checkpoints(0, 2), vec![(7, 2)]);
+		// The first summoner endowed the guild bank, no funds were minted.
+		assert_eq!(Balances::free_balance(TemplateModule::account_id(0)), 1);
+		assert_eq!(Balances::free_balance(1), 9);
+		assert_eq!(Balances::total_issuance(), 10);
+		assert_ok!(TemplateModule::do_try_state());
+	});
+}
+
+#[test]
+fn migration_keeps_current_layout_untouched() {
+	new_test_ext().execute_with(|| {
+		let index = sponsored_membership_proposal(10, 50);
+		let member = TemplateModule::members(0, 1);
+		let proposal = TemplateModule::proposals(0, 0);
+
+		assert_eq!(TemplateModule::on_runtime_upgrade(), 1);
+
+		assert_eq!(TemplateModule::members(0, 1), member);
+		assert_eq!(TemplateModule::proposals(0, 0), proposal);
+		assert_eq!(TemplateModule::proposal_queue(0, index), Some(0));
+		assert_eq!(TemplateModule::summoning_time(0), 0);
+	});
+}
+
+#[test]
+fn try_state_detects_share_mismatch() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::do_try_state());
+		TotalShares::insert(0, 2);
+		assert_err!(TemplateModule::do_try_state(), "TotalShares differs from the sum of member shares");
+	});
+}
+
+#[test]
+fn try_state_detects_unbacked_balances() {
+	new_test_ext().execute_with(|| {
+		<UserBalances<Test>>::insert(0, 2, 5);
+		assert_err!(TemplateModule::do_try_state(), "guild bank holds less than the internal balances");
+	});
+}
+
+#[test]
+fn try_state_detects_jailed_member_with_shares() {
+	new_test_ext().execute_with(|| {
+		<Members<Test>>::mutate(0, 1, |m| m.as_mut().unwrap().jailed = true);
+		assert_err!(TemplateModule::do_try_state(), "jailed member holds shares");
+	});
+}
+
+#[test]
+fn try_state_detects_queue_gap() {
+	new_test_ext().execute_with(|| {
+		ProposalQueueLength::insert(0, 1);
+		assert_err!(TemplateModule::do_try_state(), "proposal queue indices are not contiguous");
 	});
 }
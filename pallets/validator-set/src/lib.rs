@@ -0,0 +1,186 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! A validator set that changes through a configurable origin, such as passed DAO proposals.
+//!
+//! The module is the `SessionManager` of `pallet_session`: validators added or removed take
+//! effect at a session boundary, when `pallet_session` hands the new set to its session handlers,
+//! Aura and GRANDPA among them. New validators must register their session keys with
+//! `Session::set_keys` before the rotation that queues them.
+//!
+//! Validators hold no stake, so they are identified in `pallet_session::historical` by their
+//! account alone. Offences reported against a validator, such as GRANDPA equivocations, remove it
+//! from the set.
+
+use sp_std::{prelude::*, marker::PhantomData};
+use frame_support::{
+	decl_module, decl_storage, decl_event, decl_error, ensure,
+	dispatch::DispatchResult,
+	traits::{EnsureOrigin, Get},
+};
+use frame_system as system;
+use sp_runtime::{Perbill, traits::Convert};
+use sp_staking::{SessionIndex, offence::{OffenceDetails, OnOffenceHandler}};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// The pallet's configuration trait.
+pub trait Trait: system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+
+	/// Origin allowed to add and remove validators.
+	type AddRemoveOrigin: EnsureOrigin<Self::Origin>;
+
+	/// Number of validators that must remain for the chain to keep producing blocks.
+	type MinValidators: Get<u32>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as ValidatorSet {
+		/// Validators of the next session to be planned.
+		Validators get(fn validators) config(): Vec<T::AccountId>;
+
+		/// Whether the validators changed since they were last handed to `pallet_session`.
+		Changed: bool;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where AccountId = <T as system::Trait>::AccountId {
+		/// A validator was added, effective from the next session rotation. \[validator\]
+		ValidatorAdded(AccountId),
+		/// A validator was removed, effective from the next session rotation. \[validator\]
+		ValidatorRemoved(AccountId),
+		/// A validator was removed for an offence, effective from the next session rotation.
+		/// \[offender\]
+		OffenderRemoved(AccountId),
+	}
+);
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// The account is already a validator.
+		AlreadyValidator,
+		/// The account is not a validator.
+		NotValidator,
+		/// Removing the validator would leave fewer than `MinValidators`.
+		TooFewValidators,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		fn deposit_event() = default;
+
+		/// Number of validators that must remain.
+		const MinValidators: u32 = T::MinValidators::get();
+
+		/// Add `validator` to the set from the next session rotation.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(1, 2)]
+		pub fn add_validator(origin, validator: T::AccountId) -> DispatchResult {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+			let mut validators = Self::validators();
+			ensure!(!validators.contains(&validator), Error::<T>::AlreadyValidator);
+
+			validators.push(validator.clone());
+			<Validators<T>>::put(validators);
+			Changed::put(true);
+
+			Self::deposit_event(RawEvent::ValidatorAdded(validator));
+			Ok(())
+		}
+
+		/// Remove `validator` from the set from the next session rotation.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(1, 2)]
+		pub fn remove_validator(origin, validator: T::AccountId) -> DispatchResult {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+			Self::do_remove_validator(&validator)?;
+
+			Self::deposit_event(RawEvent::ValidatorRemoved(validator));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Remove `validator` from the planned set, keeping at least `MinValidators`.
+	fn do_remove_validator(validator: &T::AccountId) -> DispatchResult {
+		let mut validators = Self::validators();
+		let position = validators.iter().position(|v| v == validator).ok_or(Error::<T>::NotValidator)?;
+		ensure!(validators.len() as u32 > T::MinValidators::get(), Error::<T>::TooFewValidators);
+
+		validators.remove(position);
+		<Validators<T>>::put(validators);
+		Changed::put(true);
+		Ok(())
+	}
+}
+
+impl<T: Trait> pallet_session::SessionManager<T::AccountId> for Module<T> {
+	fn new_session(_new_index: SessionIndex) -> Option<Vec<T::AccountId>> {
+		if Changed::take() {
+			Some(Self::validators())
+		} else {
+			None
+		}
+	}
+
+	fn end_session(_end_index: SessionIndex) {}
+
+	fn start_session(_start_index: SessionIndex) {}
+}
+
+impl<T: Trait> pallet_session::historical::SessionManager<T::AccountId, ()> for Module<T> {
+	fn new_session(new_index: SessionIndex) -> Option<Vec<(T::AccountId, ())>> {
+		<Self as pallet_session::SessionManager<_>>::new_session(new_index)
+			.map(|validators| validators.into_iter().map(|v| (v, ())).collect())
+	}
+
+	fn end_session(end_index: SessionIndex) {
+		<Self as pallet_session::SessionManager<_>>::end_session(end_index)
+	}
+
+	fn start_session(start_index: SessionIndex) {
+		<Self as pallet_session::SessionManager<_>>::start_session(start_index)
+	}
+}
+
+/// The full identification of a validator in `pallet_session::historical`, which is empty as
+/// validators hold no stake.
+pub struct FullIdentificationOf<T>(PhantomData<T>);
+
+impl<T: Trait> Convert<T::AccountId, Option<()>> for FullIdentificationOf<T> {
+	fn convert(_validator: T::AccountId) -> Option<()> {
+		Some(())
+	}
+}
+
+/// Removes offenders from the validator set, regardless of the slash fraction.
+///
+/// Offenders that already left the set are ignored, and the last `MinValidators` validators are
+/// kept so that the chain does not stall.
+impl<T: Trait, F> OnOffenceHandler<T::AccountId, (T::AccountId, F)> for Module<T> {
+	fn on_offence(
+		offenders: &[OffenceDetails<T::AccountId, (T::AccountId, F)>],
+		_slash_fraction: &[Perbill],
+		_session: SessionIndex,
+	) -> Result<(), ()> {
+		for details in offenders {
+			let (offender, _) = &details.offender;
+			if Self::do_remove_validator(offender).is_ok() {
+				Self::deposit_event(RawEvent::OffenderRemoved(offender.clone()));
+			}
+		}
+		Ok(())
+	}
+
+	fn can_report() -> bool {
+		true
+	}
+}
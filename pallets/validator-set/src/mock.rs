@@ -0,0 +1,90 @@
+// Creating mock runtime here
+
+use crate::{Module, Trait, GenesisConfig};
+use sp_core::H256;
+use frame_support::{impl_outer_origin, impl_outer_event, parameter_types, weights::Weight};
+use sp_runtime::{
+	traits::{BlakeTwo256, IdentityLookup}, testing::Header, Perbill,
+};
+use frame_system::{self as system, EnsureRoot};
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+mod validator_set {
+	pub use crate::Event;
+}
+
+impl_outer_event! {
+	pub enum TestEvent for Test {
+		system<T>,
+		validator_set<T>,
+	}
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+impl system::Trait for Test {
+	type Origin = Origin;
+	type Call = ();
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = TestEvent;
+	type BlockHashCount = BlockHashCount;
+	type MaximumBlockWeight = MaximumBlockWeight;
+	type DbWeight = ();
+	type BlockExecutionWeight = ();
+	type ExtrinsicBaseWeight = ();
+	type MaximumExtrinsicWeight = MaximumBlockWeight;
+	type MaximumBlockLength = MaximumBlockLength;
+	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type ModuleToIndex = ();
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+}
+parameter_types! {
+	pub const MinValidators: u32 = 2;
+}
+impl Trait for Test {
+	type Event = TestEvent;
+	type AddRemoveOrigin = EnsureRoot<u64>;
+	type MinValidators = MinValidators;
+}
+pub type System = system::Module<Test>;
+pub type ValidatorSet = Module<Test>;
+
+/// Genesis with validators 1, 2 and 3.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	GenesisConfig::<Test> {
+		validators: vec![1, 2, 3],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// The most recently deposited event of this pallet.
+pub fn last_event() -> crate::Event<Test> {
+	System::events().into_iter().rev()
+		.find_map(|record| match record.event {
+			TestEvent::validator_set(event) => Some(event),
+			_ => None,
+		})
+		.expect("a validator set event was deposited")
+}
@@ -0,0 +1,71 @@
+// Tests to be written here
+
+use crate::{Error, RawEvent, mock::*};
+use frame_support::{assert_ok, assert_noop};
+use pallet_session::SessionManager;
+use sp_runtime::{DispatchError, Perbill};
+use sp_staking::offence::{OffenceDetails, OnOffenceHandler};
+
+#[test]
+fn genesis_validators_are_not_replanned() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(ValidatorSet::validators(), vec![1, 2, 3]);
+		assert_eq!(ValidatorSet::new_session(1), None);
+	});
+}
+
+#[test]
+fn added_validator_is_planned_at_next_session() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(ValidatorSet::add_validator(Origin::signed(1), 4), DispatchError::BadOrigin);
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 4));
+		assert_eq!(last_event(), RawEvent::ValidatorAdded(4));
+		assert_noop!(ValidatorSet::add_validator(Origin::root(), 4), Error::<Test>::AlreadyValidator);
+
+		assert_eq!(ValidatorSet::new_session(1), Some(vec![1, 2, 3, 4]));
+		// Unchanged afterwards, so the session pallet keeps the planned set.
+		assert_eq!(ValidatorSet::new_session(2), None);
+	});
+}
+
+#[test]
+fn removal_keeps_minimum_validators() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(ValidatorSet::remove_validator(Origin::signed(1), 2), DispatchError::BadOrigin);
+		assert_noop!(ValidatorSet::remove_validator(Origin::root(), 4), Error::<Test>::NotValidator);
+
+		assert_ok!(ValidatorSet::remove_validator(Origin::root(), 2));
+		assert_eq!(last_event(), RawEvent::ValidatorRemoved(2));
+		assert_noop!(ValidatorSet::remove_validator(Origin::root(), 1), Error::<Test>::TooFewValidators);
+
+		assert_eq!(ValidatorSet::new_session(1), Some(vec![1, 3]));
+	});
+}
+
+fn report(offenders: &[u64]) {
+	let details: Vec<_> = offenders.iter()
+		.map(|&offender| OffenceDetails { offender: (offender, ()), reporters: vec![] })
+		.collect();
+	let fractions = vec![Perbill::from_percent(100); details.len()];
+	assert_eq!(<ValidatorSet as OnOffenceHandler<u64, (u64, ())>>::on_offence(&details, &fractions, 0), Ok(()));
+}
+
+#[test]
+fn offenders_are_removed_at_next_session() {
+	new_test_ext().execute_with(|| {
+		report(&[2]);
+		assert_eq!(last_event(), RawEvent::OffenderRemoved(2));
+		assert_eq!(ValidatorSet::validators(), vec![1, 3]);
+		assert_eq!(ValidatorSet::new_session(1), Some(vec![1, 3]));
+	});
+}
+
+#[test]
+fn offences_keep_minimum_validators() {
+	new_test_ext().execute_with(|| {
+		// Reports against unknown accounts are ignored.
+		report(&[4, 1, 3]);
+		assert_eq!(ValidatorSet::validators(), vec![2, 3]);
+		assert_eq!(last_event(), RawEvent::OffenderRemoved(1));
+	});
+}
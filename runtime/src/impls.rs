@@ -0,0 +1,250 @@
+//! Some configurable implementations as associated type for the runtime.
+
+use frame_support::{
+	traits::{Currency, FindAuthor, Get, Imbalance, OnUnbalanced},
+	weights::{WeightToFeePolynomial, WeightToFeeCoefficients, WeightToFeeCoefficient},
+};
+use smallvec::smallvec;
+use sp_runtime::{ConsensusEngineId, FixedPointNumber, Perbill, Perquintill, traits::Convert};
+use transaction_payment::Multiplier;
+use crate::{
+	AccountId, Aura, AuthorFeeShare, AvailableBlockRatio, Balance, Balances, MaximumBlockWeight, Session,
+	System, TemplateModule,
+};
+
+type NegativeImbalance = <Balances as Currency<AccountId>>::NegativeImbalance;
+
+/// Finds the account of the validator whose Aura key authored a block.
+pub struct AuraAccountAdapter;
+
+impl FindAuthor<AccountId> for AuraAccountAdapter {
+	fn find_author<'a, I>(digests: I) -> Option<AccountId> where
+		I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>
+	{
+		let index = Aura::find_author(digests)?;
+		// Session hands Aura the keys of its validators in order, so the author's index is the
+		// same in both. Session keys are set independently of accounts.
+		Session::validators().get(index as usize).cloned()
+	}
+}
+
+impl AuraAccountAdapter {
+	/// The account that authored the block being built, if it has an Aura pre-runtime digest.
+	pub fn author() -> Option<AccountId> {
+		let digest = System::digest();
+		Self::find_author(digest.logs().iter().filter_map(|d| d.as_pre_runtime()))
+	}
+}
+
+/// Pays `AuthorFeeShare` of transaction fees to the block author and the rest to the guild bank.
+///
+/// Blocks without an author, such as those sealed manually, pay all fees to the guild bank.
+pub struct DealWithFees;
+
+impl OnUnbalanced<NegativeImbalance> for DealWithFees {
+	fn on_nonzero_unbalanced(fees: NegativeImbalance) {
+		let author_fees = AuthorFeeShare::get() * fees.peek();
+		let (to_author, to_guild) = fees.split(author_fees);
+		match AuraAccountAdapter::author() {
+			Some(author) => {
+				Balances::resolve_creating(&author, to_author);
+				TemplateModule::on_unbalanced(to_guild);
+			}
+			None => TemplateModule::on_unbalanced(to_guild.merge(to_author)),
+		}
+	}
+}
+
+/// Converts weight to fees with `weight + weight^2 / 10^9`.
+///
+/// Typical transactions pay about their weight, while a single transaction filling a large part
+/// of the block pays disproportionately more.
+pub struct WeightToFee;
+
+impl WeightToFeePolynomial for WeightToFee {
+	type Balance = Balance;
+
+	fn polynomial() -> WeightToFeeCoefficients<Self::Balance> {
+		smallvec![
+			WeightToFeeCoefficient {
+				coeff_integer: 1,
+				coeff_frac: Perbill::zero(),
+				negative: false,
+				degree: 1,
+			},
+			WeightToFeeCoefficient {
+				coeff_integer: 0,
+				coeff_frac: Perbill::from_parts(1),
+				negative: false,
+				degree: 2,
+			},
+		]
+	}
+}
+
+/// Update the fee multiplier of the next block based on how full the previous one was.
+///
+/// Blocks are compared against `T` of the weight available to normal transactions, that is
+/// `AvailableBlockRatio` of `MaximumBlockWeight`. Blocks fuller than the target make the next
+/// block's weight fees more expensive, emptier blocks make them cheaper, down to no weight fees
+/// at all. Following the [Web3 Foundation research](https://research.web3.foundation/en/latest/polkadot/Token%20Economics.html#relay-chain-transaction-fees)
+/// the change per block is `v * s + v^2 * s^2 / 2`, where `s` is the block's distance from the
+/// target as a fraction of the available weight.
+pub struct TargetedFeeAdjustment<T>(sp_std::marker::PhantomData<T>);
+
+impl<T: Get<Perquintill>> Convert<Multiplier, Multiplier> for TargetedFeeAdjustment<T> {
+	fn convert(multiplier: Multiplier) -> Multiplier {
+		let max_weight = AvailableBlockRatio::get() * MaximumBlockWeight::get();
+		let block_weight = System::all_extrinsics_weight().total().min(max_weight) as u128;
+		let target_weight = (T::get() * max_weight) as u128;
+
+		// determines if the first_term is positive
+		let positive = block_weight >= target_weight;
+		let diff_abs = block_weight.max(target_weight) - block_weight.min(target_weight);
+		let diff = Multiplier::saturating_from_rational(diff_abs, max_weight.max(1));
+		let diff_squared = diff.saturating_mul(diff);
+
+		// v = 0.00004, so a full block raises fees by about 0.003% on top of the target.
+		let v = Multiplier::saturating_from_rational(4, 100_000);
+		// v^2 / 2 = 0.0000000008
+		let v_squared_2 = Multiplier::saturating_from_rational(8, 10_000_000_000u64);
+
+		let first_term = v.saturating_mul(diff);
+		let second_term = v_squared_2.saturating_mul(diff_squared);
+
+		if positive {
+			multiplier.saturating_add(first_term.saturating_add(second_term))
+		} else {
+			// first_term > second_term as diff < 1, so this never increases the multiplier.
+			multiplier
+				.saturating_sub(first_term.saturating_sub(second_term))
+				// At -1 transactions pay no weight fees at all. Going further would only delay
+				// fees from rising again once the chain gets busy.
+				.max(Multiplier::saturating_from_integer(-1))
+		}
+	}
+}
+
+/// The key used by validators to sign and submit GRANDPA equivocation reports.
+///
+/// Reports are submitted from the first key of this type in a validator's keystore, which has to
+/// be inserted with `author_insertKey` alongside the session keys.
+pub mod report {
+	use sp_core::crypto::KeyTypeId;
+	use sp_runtime::traits::Verify;
+	use crate::Signature;
+
+	pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"fish");
+
+	mod app {
+		use sp_application_crypto::{app_crypto, sr25519};
+		app_crypto!(sr25519, super::KEY_TYPE);
+	}
+
+	/// Identity of an equivocation reporter.
+	pub type ReporterId = app::Public;
+
+	/// Signs equivocation reports as the account of a `ReporterId`.
+	pub struct ReporterAppCrypto;
+
+	impl system::offchain::AppCrypto<<Signature as Verify>::Signer, Signature> for ReporterAppCrypto {
+		type RuntimeAppPublic = ReporterId;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::weights::Weight;
+	use sp_runtime::traits::Zero;
+	use crate::{Runtime, TargetBlockFullness};
+
+	type Adjustment = TargetedFeeAdjustment<TargetBlockFullness>;
+
+	fn max_normal() -> Weight {
+		AvailableBlockRatio::get() * MaximumBlockWeight::get()
+	}
+
+	fn target() -> Weight {
+		TargetBlockFullness::get() * max_normal()
+	}
+
+	fn run_with_system_weight<F: FnMut()>(weight: Weight, mut assertions: F) {
+		let mut t: sp_io::TestExternalities = system::GenesisConfig::default()
+			.build_storage::<Runtime>()
+			.unwrap()
+			.into();
+		t.execute_with(|| {
+			System::set_block_limits(weight, 0);
+			assertions()
+		});
+	}
+
+	#[test]
+	fn polynomial_weight_to_fee() {
+		assert_eq!(WeightToFee::calc(&0), 0);
+		assert_eq!(WeightToFee::calc(&1_000), 1_000);
+		assert_eq!(WeightToFee::calc(&1_000_000_000), 2_000_000_000);
+		// A transaction filling the block pays a thousand times more than its weight.
+		let full = max_normal();
+		assert!(WeightToFee::calc(&full) > 1_000 * full as Balance);
+	}
+
+	#[test]
+	fn multiplier_is_stable_at_target() {
+		run_with_system_weight(target(), || {
+			let multiplier = Multiplier::saturating_from_rational(1, 2);
+			assert_eq!(Adjustment::convert(multiplier), multiplier);
+		});
+	}
+
+	#[test]
+	fn multiplier_rises_under_sustained_full_blocks() {
+		run_with_system_weight(max_normal(), || {
+			let mut multiplier = Multiplier::zero();
+			let mut blocks = 0;
+			// Weight fees double within about two and a half days of full blocks.
+			while multiplier < Multiplier::saturating_from_integer(1) {
+				let next = Adjustment::convert(multiplier);
+				assert!(next > multiplier);
+				multiplier = next;
+				blocks += 1;
+			}
+			assert!(blocks < 2 * 24 * 600 + 12 * 600, "took {} blocks", blocks);
+		});
+	}
+
+	#[test]
+	fn multiplier_converges_to_no_weight_fees_when_idle() {
+		run_with_system_weight(0, || {
+			let mut multiplier = Multiplier::zero();
+			for _ in 0..150_000 {
+				let next = Adjustment::convert(multiplier);
+				assert!(next <= multiplier);
+				multiplier = next;
+			}
+			assert_eq!(multiplier, Multiplier::saturating_from_integer(-1));
+			assert_eq!(Adjustment::convert(multiplier), multiplier);
+		});
+	}
+
+	#[test]
+	fn multiplier_recovers_after_congestion() {
+		let mut multiplier = Multiplier::zero();
+		run_with_system_weight(max_normal(), || {
+			for _ in 0..1_000 {
+				multiplier = Adjustment::convert(multiplier);
+			}
+		});
+		assert!(multiplier > Multiplier::zero());
+		run_with_system_weight(target() / 2, || {
+			let peak = multiplier;
+			for _ in 0..1_000 {
+				multiplier = Adjustment::convert(multiplier);
+			}
+			assert!(multiplier < peak);
+		});
+	}
+}
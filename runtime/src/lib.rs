@@ -10,17 +10,21 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 use sp_std::prelude::*;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
+use codec::Encode;
 use sp_runtime::{
-	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys, MultiSignature,
+	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys, MultiSignature, ModuleId,
+	SaturatedConversion,
 	transaction_validity::{TransactionValidity, TransactionSource},
 };
 use sp_runtime::traits::{
 	BlakeTwo256, Block as BlockT, IdentityLookup, Verify, IdentifyAccount, NumberFor, Saturating,
+	ConvertInto, OpaqueKeys, Extrinsic as ExtrinsicT,
 };
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use grandpa::{AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
 use grandpa::fg_primitives;
+use session::historical as session_historical;
 use sp_version::RuntimeVersion;
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
@@ -30,19 +34,24 @@ use sp_version::NativeVersion;
 pub use sp_runtime::BuildStorage;
 pub use timestamp::Call as TimestampCall;
 pub use balances::Call as BalancesCall;
-pub use sp_runtime::{Permill, Perbill};
+pub use sp_runtime::{Permill, Perbill, Perquintill};
 pub use frame_support::{
 	construct_runtime, parameter_types, StorageValue,
-	traits::{KeyOwnerProofSystem, Randomness},
+	traits::{Get, KeyOwnerProofSystem, Randomness},
 	weights::{
-		Weight, IdentityFee,
-		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
+		Weight,
+		constants::{
+			BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_MILLIS, WEIGHT_PER_SECOND,
+		},
 	},
 };
 
 /// Importing a template pallet
 pub use template;
 
+pub mod impls;
+use impls::{DealWithFees, TargetedFeeAdjustment, WeightToFee, report::ReporterAppCrypto};
+
 /// An index to a block.
 pub type BlockNumber = u32;
 
@@ -98,10 +107,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("node-template"),
 	impl_name: create_runtime_str!("node-template"),
 	authoring_version: 1,
-	spec_version: 1,
+	spec_version: 26,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 19,
 };
 
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
@@ -193,11 +202,57 @@ impl aura::Trait for Runtime {
 	type AuthorityId = AuraId;
 }
 
+parameter_types! {
+	pub const SessionPeriod: BlockNumber = HOURS;
+	pub const SessionOffset: BlockNumber = 0;
+	pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(17);
+}
+
+impl session::Trait for Runtime {
+	type Event = Event;
+	type ValidatorId = AccountId;
+	type ValidatorIdOf = ConvertInto;
+	type ShouldEndSession = session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	type NextSessionRotation = session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	/// Validators are added and removed through the DAO, and their keys kept for historical
+	/// proofs of key ownership.
+	type SessionManager = session::historical::NoteHistoricalRoot<Self, ValidatorSet>;
+	/// Hands authority changes to Aura and GRANDPA.
+	type SessionHandler = <opaque::SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+	type Keys = opaque::SessionKeys;
+	type DisabledValidatorsThreshold = DisabledValidatorsThreshold;
+}
+
+impl session::historical::Trait for Runtime {
+	type FullIdentification = ();
+	type FullIdentificationOf = validator_set::FullIdentificationOf<Runtime>;
+}
+
+impl offences::Trait for Runtime {
+	type Event = Event;
+	type IdentificationTuple = session::historical::IdentificationTuple<Self>;
+	/// Offenders are removed from the validator set.
+	type OnOffenceHandler = ValidatorSet;
+}
+
+parameter_types! {
+	/// Keep at least one validator, so that the chain does not stall.
+	pub const MinValidators: u32 = 1;
+	/// Only the DAO summoned at genesis governs the validator set.
+	pub const GenesisDao: template::DaoId = template::GENESIS_DAO;
+}
+
+impl validator_set::Trait for Runtime {
+	type Event = Event;
+	type AddRemoveOrigin = system::EnsureOneOf<AccountId, system::EnsureRoot<AccountId>, template::EnsureDaoId<GenesisDao>>;
+	type MinValidators = MinValidators;
+}
+
 impl grandpa::Trait for Runtime {
 	type Event = Event;
 	type Call = Call;
 
-	type KeyOwnerProofSystem = ();
+	type KeyOwnerProofSystem = Historical;
 
 	type KeyOwnerProof =
 		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, GrandpaId)>>::Proof;
@@ -207,7 +262,64 @@ impl grandpa::Trait for Runtime {
 		GrandpaId,
 	)>>::IdentificationTuple;
 
-	type HandleEquivocation = ();
+	type HandleEquivocation = grandpa::EquivocationHandler<
+		Self::KeyOwnerIdentification,
+		ReporterAppCrypto,
+		Runtime,
+		Offences,
+	>;
+}
+
+impl<LocalCall> system::offchain::CreateSignedTransaction<LocalCall> for Runtime where
+	Call: From<LocalCall>,
+{
+	fn create_transaction<C: system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: Call,
+		public: <Signature as Verify>::Signer,
+		account: AccountId,
+		nonce: Index,
+	) -> Option<(Call, <UncheckedExtrinsic as ExtrinsicT>::SignaturePayload)> {
+		// Valid for as long as block hashes are kept, rounded down to a power of two.
+		let period = BlockHashCount::get()
+			.checked_next_power_of_two()
+			.map(|c| c / 2)
+			.unwrap_or(2) as u64;
+		let current_block = System::block_number()
+			.saturated_into::<u64>()
+			// The `System::block_number` is initialized with `n+1`,
+			// so the actual block number is `n`.
+			.saturating_sub(1);
+		let extra: SignedExtra = (
+			system::CheckSpecVersion::<Runtime>::new(),
+			system::CheckTxVersion::<Runtime>::new(),
+			system::CheckGenesis::<Runtime>::new(),
+			system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
+			system::CheckNonce::<Runtime>::from(nonce),
+			system::CheckWeight::<Runtime>::new(),
+			template::ValidateDaoCall::<Runtime>::new(),
+			transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+		);
+		let raw_payload = SignedPayload::new(call, extra)
+			.map_err(|e| {
+				frame_support::debug::warn!("Unable to create signed payload: {:?}", e);
+			})
+			.ok()?;
+		let signature = raw_payload.using_encoded(|payload| C::sign(payload, public))?;
+		let (call, extra, _) = raw_payload.deconstruct();
+		Some((call, (account, signature, extra)))
+	}
+}
+
+impl system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> system::offchain::SendTransactionTypes<C> for Runtime where
+	Call: From<C>,
+{
+	type Extrinsic = UncheckedExtrinsic;
+	type OverarchingCall = Call;
 }
 
 parameter_types! {
@@ -230,21 +342,26 @@ impl balances::Trait for Runtime {
 	type Balance = Balance;
 	/// The ubiquitous event type.
 	type Event = Event;
-	type DustRemoval = ();
+	/// Dust is paid into the DAO's guild bank.
+	type DustRemoval = TemplateModule;
 	type ExistentialDeposit = ExistentialDeposit;
 	type AccountStore = System;
 }
 
 parameter_types! {
 	pub const TransactionByteFee: Balance = 1;
+	/// Part of the fees going to the block author, the rest is paid into the guild bank.
+	pub const AuthorFeeShare: Perbill = Perbill::from_percent(20);
+	/// Fees rise when blocks are fuller than this and fall when they are emptier.
+	pub const TargetBlockFullness: Perquintill = Perquintill::from_percent(25);
 }
 
 impl transaction_payment::Trait for Runtime {
 	type Currency = balances::Module<Runtime>;
-	type OnTransactionPayment = ();
+	type OnTransactionPayment = DealWithFees;
 	type TransactionByteFee = TransactionByteFee;
-	type WeightToFee = IdentityFee<Balance>;
-	type FeeMultiplierUpdate = ();
+	type WeightToFee = WeightToFee;
+	type FeeMultiplierUpdate = TargetedFeeAdjustment<TargetBlockFullness>;
 }
 
 impl sudo::Trait for Runtime {
@@ -252,9 +369,86 @@ impl sudo::Trait for Runtime {
 	type Call = Call;
 }
 
-/// Used for the module template in `./template.rs`
+parameter_types! {
+	pub const DaoModuleId: ModuleId = ModuleId(*b"py/moloc");
+	// The genesis DAO starts out with the following parameters and changes them through
+	// parameter-change proposals.
+	pub const PeriodDuration: BlockNumber = HOURS;
+	/// A week of voting.
+	pub const VotingPeriodLength: u64 = 7 * 24;
+	/// Three days to ragequit before a passed proposal takes effect.
+	pub const GracePeriodLength: u64 = 3 * 24;
+	pub const ProposalDeposit: Balance = 1_000_000_000_000;
+	pub const ProcessingReward: Balance = 100_000_000_000;
+	pub const DilutionBound: u128 = 3;
+	/// Funding and membership pass on a simple majority, parameter changes and actions such as
+	/// runtime upgrades or validator changes need two thirds. A fifth of all shares must vote,
+	/// a third on guild kicks.
+	pub const DaoThresholds: template::Thresholds = template::Thresholds {
+		funding: template::Threshold { approval: Perbill::from_percent(50), quorum: Perbill::from_percent(20) },
+		membership: template::Threshold { approval: Perbill::from_percent(50), quorum: Perbill::from_percent(20) },
+		parameter_change: template::Threshold {
+			approval: Perbill::from_rational_approximation(2u32, 3u32),
+			quorum: Perbill::from_percent(20),
+		},
+		action: template::Threshold {
+			approval: Perbill::from_rational_approximation(2u32, 3u32),
+			quorum: Perbill::from_percent(20),
+		},
+		guild_kick: template::Threshold { approval: Perbill::from_percent(50), quorum: Perbill::from_percent(33) },
+	};
+	/// Votes per member and hour whose fee is refunded.
+	pub const MaxVotesPerPeriod: u32 = 10;
+	/// Votes carry the weight of at most 155 delegators, five per member over three levels.
+	pub const MaxDelegationDepth: u32 = 3;
+	pub const MaxDelegators: u32 = 5;
+	/// Members may commit shares to quadratic votes on up to 16 unprocessed proposals.
+	pub const MaxCommitments: u32 = 16;
+	/// DAOs may appoint up to 9 guardians.
+	pub const MaxGuardians: u32 = 9;
+	pub const MaxGrants: u32 = 16;
+	pub const MaxMilestones: u32 = 10;
+	pub const MaxMilestoneApprovals: u32 = 16;
+	pub const MaxCheckpoints: u32 = 64;
+	/// Passed action proposals may use up to a tenth of a block.
+	pub const MaxActionWeight: Weight = 200 * WEIGHT_PER_MILLIS;
+}
+
+/// Used for the DAO module in `pallets/template`
 impl template::Trait for Runtime {
 	type Event = Event;
+	type Origin = Origin;
+	type Proposal = Call;
+	type MaxActionWeight = MaxActionWeight;
+	type Currency = Balances;
+	type ModuleId = DaoModuleId;
+	type PeriodDuration = PeriodDuration;
+	type VotingPeriodLength = VotingPeriodLength;
+	type GracePeriodLength = GracePeriodLength;
+	type ProposalDeposit = ProposalDeposit;
+	type ProcessingReward = ProcessingReward;
+	type DilutionBound = DilutionBound;
+	type Thresholds = DaoThresholds;
+	type MaxVotesPerPeriod = MaxVotesPerPeriod;
+	type MaxDelegationDepth = MaxDelegationDepth;
+	type MaxDelegators = MaxDelegators;
+	type MaxCommitments = MaxCommitments;
+	type DaoOrigin = template::EnsureDao;
+	type MaxGuardians = MaxGuardians;
+	type MaxGrants = MaxGrants;
+	type MaxMilestones = MaxMilestones;
+	type MaxMilestoneApprovals = MaxMilestoneApprovals;
+	type MaxCheckpoints = MaxCheckpoints;
+	type TemplateSummoners = TemplateSummoners;
+}
+
+/// The sudo key summons the DAO when a chain upgrades from the node template, and endows its
+/// guild bank.
+pub struct TemplateSummoners;
+impl Get<Vec<(AccountId, template::Shares)>> for TemplateSummoners {
+	fn get() -> Vec<(AccountId, template::Shares)> {
+		vec![(Sudo::key(), 1)]
+	}
 }
 
 construct_runtime!(
@@ -266,13 +460,18 @@ construct_runtime!(
 		System: system::{Module, Call, Config, Storage, Event<T>},
 		RandomnessCollectiveFlip: randomness_collective_flip::{Module, Call, Storage},
 		Timestamp: timestamp::{Module, Call, Storage, Inherent},
+		// Validators must be planned before `Session` builds its genesis.
+		ValidatorSet: validator_set::{Module, Call, Storage, Event<T>, Config<T>},
+		Session: session::{Module, Call, Storage, Event, Config<T>},
+		Historical: session_historical::{Module},
+		Offences: offences::{Module, Call, Storage, Event},
 		Aura: aura::{Module, Config<T>, Inherent(Timestamp)},
 		Grandpa: grandpa::{Module, Call, Storage, Config, Event},
 		Balances: balances::{Module, Call, Storage, Config<T>, Event<T>},
 		TransactionPayment: transaction_payment::{Module, Storage},
 		Sudo: sudo::{Module, Call, Config<T>, Storage, Event<T>},
-		// Used for the module template in `./template.rs`
-		TemplateModule: template::{Module, Call, Storage, Event<T>},
+		// Used for the DAO module in `pallets/template`
+		TemplateModule: template::{Module, Call, Config<T>, Storage, Event<T>, Origin},
 	}
 );
 
@@ -294,8 +493,11 @@ pub type SignedExtra = (
 	system::CheckEra<Runtime>,
 	system::CheckNonce<Runtime>,
 	system::CheckWeight<Runtime>,
+	template::ValidateDaoCall<Runtime>,
 	transaction_payment::ChargeTransactionPayment<Runtime>
 );
+/// The payload being signed in transactions.
+pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
 /// Extrinsic type that has already been checked.
@@ -386,29 +588,43 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl template_runtime_api::DaoApi<Block, AccountId, BlockNumber> for Runtime {
+		fn try_state() -> Result<(), Vec<u8>> {
+			TemplateModule::do_try_state().map_err(|e| e.as_bytes().to_vec())
+		}
+
+		fn shares_at(dao_id: u32, account: AccountId, block: BlockNumber) -> u128 {
+			TemplateModule::shares_at(dao_id, &account, block)
+		}
+	}
+
 	impl fg_primitives::GrandpaApi<Block> for Runtime {
 		fn grandpa_authorities() -> GrandpaAuthorityList {
 			Grandpa::grandpa_authorities()
 		}
 
 		fn submit_report_equivocation_extrinsic(
-			_equivocation_proof: fg_primitives::EquivocationProof<
+			equivocation_proof: fg_primitives::EquivocationProof<
 				<Block as BlockT>::Hash,
 				NumberFor<Block>,
 			>,
-			_key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
+			key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
 		) -> Option<()> {
-			None
+			let key_owner_proof = key_owner_proof.decode()?;
+
+			Grandpa::submit_report_equivocation_extrinsic(
+				equivocation_proof,
+				key_owner_proof,
+			)
 		}
 
 		fn generate_key_ownership_proof(
 			_set_id: fg_primitives::SetId,
-			_authority_id: GrandpaId,
+			authority_id: GrandpaId,
 		) -> Option<fg_primitives::OpaqueKeyOwnershipProof> {
-			// NOTE: this is the only implementation possible since we've
-			// defined our key owner proof type as a bottom type (i.e. a type
-			// with no values).
-			None
+			Historical::prove((fg_primitives::KEY_TYPE, authority_id))
+				.map(|p| p.encode())
+				.map(fg_primitives::OpaqueKeyOwnershipProof::new)
 		}
 	}
 }
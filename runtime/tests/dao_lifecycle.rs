@@ -0,0 +1,452 @@
+//! End-to-end tests driving `Executive` with signed extrinsics through whole DAO lifecycles.
+//!
+//! These exercise the runtime configuration itself: the `SignedExtra` tuple, fee charging through
+//! `ChargeTransactionPayment` and the DAO's parameters as set in `lib.rs`.
+
+use codec::Encode;
+use frame_support::traits::{Get, KeyOwnerProofSystem};
+use node_template_runtime::{
+	AccountId, AuraConfig, AuthorFeeShare, Balance, BalancesCall, BalancesConfig, BuildStorage, Call,
+	Executive, ExistentialDeposit, GenesisConfig, GracePeriodLength, GrandpaConfig, Header, Index,
+	PeriodDuration, ProcessingReward, ProposalDeposit, Runtime, SessionConfig, SessionPeriod, SignedExtra,
+	SudoConfig, SystemConfig, TemplateModuleConfig, TimestampCall, UncheckedExtrinsic, ValidatorSetConfig,
+	VotingPeriodLength, SLOT_DURATION, WASM_BINARY, opaque::SessionKeys,
+};
+use sp_consensus_aura::AURA_ENGINE_ID;
+use sp_core::{ed25519, Pair, H256};
+use sp_keyring::AccountKeyring;
+use sp_runtime::{
+	ApplyExtrinsicResult, DispatchError,
+	generic::{Digest, DigestItem, Era, SignedPayload},
+	traits::Header as HeaderT,
+	transaction_validity::{InvalidTransaction, TransactionValidityError},
+};
+
+type System = system::Module<Runtime>;
+type Balances = balances::Module<Runtime>;
+type TransactionPayment = transaction_payment::Module<Runtime>;
+type Dao = template::Module<Runtime>;
+type Aura = aura::Module<Runtime>;
+type Session = session::Module<Runtime>;
+type ValidatorSet = validator_set::Module<Runtime>;
+type Historical = session::historical::Module<Runtime>;
+
+const ENDOWMENT: Balance = 1 << 60;
+const TRIBUTE: Balance = 1_000_000_000_000_000;
+
+/// Genesis mirroring the development chain spec: Alice is the only authority and summons the DAO,
+/// and every test account is endowed.
+fn new_test_ext() -> sp_io::TestExternalities {
+	test_ext_with_session_keys(session_keys(AccountKeyring::Alice))
+}
+
+/// Like `new_test_ext`, with Alice validating with `keys`.
+fn test_ext_with_session_keys(keys: SessionKeys) -> sp_io::TestExternalities {
+	let accounts = [AccountKeyring::Alice, AccountKeyring::Bob, AccountKeyring::Charlie];
+	GenesisConfig {
+		system: Some(SystemConfig {
+			code: WASM_BINARY.to_vec(),
+			changes_trie_config: Default::default(),
+		}),
+		balances: Some(BalancesConfig {
+			balances: accounts.iter().map(|k| (k.to_account_id(), ENDOWMENT)).collect(),
+		}),
+		validator_set: Some(ValidatorSetConfig {
+			validators: vec![account(AccountKeyring::Alice)],
+		}),
+		session: Some(SessionConfig {
+			keys: vec![(
+				account(AccountKeyring::Alice),
+				account(AccountKeyring::Alice),
+				keys,
+			)],
+		}),
+		aura: Some(AuraConfig {
+			authorities: vec![],
+		}),
+		grandpa: Some(GrandpaConfig {
+			authorities: vec![],
+		}),
+		sudo: Some(SudoConfig {
+			key: AccountKeyring::Alice.to_account_id(),
+		}),
+		template_module: Some(TemplateModuleConfig {
+			members: vec![(AccountKeyring::Alice.to_account_id(), 1)],
+		}),
+	}.build_storage().unwrap().into()
+}
+
+/// Aura and GRANDPA session keys derived from the same seed as `keyring`.
+fn session_keys(keyring: AccountKeyring) -> SessionKeys {
+	let grandpa = ed25519::Pair::from_string(&keyring.to_seed(), None).expect("static values are valid; qed");
+	SessionKeys {
+		aura: keyring.public().into(),
+		grandpa: grandpa.public().into(),
+	}
+}
+
+/// Sign `call` as `signer` with the runtime's full `SignedExtra`.
+fn sign(call: Call, signer: AccountKeyring, nonce: Index) -> UncheckedExtrinsic {
+	let extra: SignedExtra = (
+		system::CheckSpecVersion::new(),
+		system::CheckTxVersion::new(),
+		system::CheckGenesis::new(),
+		system::CheckEra::from(Era::Immortal),
+		system::CheckNonce::from(nonce),
+		system::CheckWeight::new(),
+		template::ValidateDaoCall::new(),
+		transaction_payment::ChargeTransactionPayment::from(0),
+	);
+	let payload = SignedPayload::new(call, extra).expect("additional signed data is available; qed");
+	let signature = payload.using_encoded(|payload| signer.sign(payload));
+	let (call, extra, _) = payload.deconstruct();
+	UncheckedExtrinsic::new_signed(call, signer.to_account_id(), signature.into(), extra)
+}
+
+/// Builds blocks on top of each other, jumping straight to the requested block numbers.
+struct Chain {
+	parent_hash: H256,
+}
+
+impl Chain {
+	fn new() -> Self {
+		Chain { parent_hash: System::block_hash(0) }
+	}
+
+	/// Execute a block at height `number` containing the timestamp inherent and `extrinsics`.
+	///
+	/// The block has no Aura pre-runtime digest, like manually sealed blocks.
+	fn block(&mut self, number: u32, extrinsics: Vec<UncheckedExtrinsic>) -> Vec<ApplyExtrinsicResult> {
+		self.execute(number, Default::default(), extrinsics)
+	}
+
+	/// Execute a block at height `number` authored by the Aura authority of its slot.
+	fn authored_block(&mut self, number: u32, extrinsics: Vec<UncheckedExtrinsic>) -> Vec<ApplyExtrinsicResult> {
+		let slot = number as u64;
+		let digest = Digest { logs: vec![DigestItem::PreRuntime(AURA_ENGINE_ID, slot.encode())] };
+		self.execute(number, digest, extrinsics)
+	}
+
+	fn execute(
+		&mut self,
+		number: u32,
+		digest: Digest<H256>,
+		extrinsics: Vec<UncheckedExtrinsic>,
+	) -> Vec<ApplyExtrinsicResult> {
+		let header = Header::new(number, Default::default(), Default::default(), self.parent_hash, digest);
+		Executive::initialize_block(&header);
+
+		let timestamp = Call::Timestamp(TimestampCall::set(number as u64 * SLOT_DURATION));
+		Executive::apply_extrinsic(UncheckedExtrinsic::new_unsigned(timestamp))
+			.expect("timestamp inherent is valid; qed")
+			.expect("timestamp inherent succeeds; qed");
+
+		let results = extrinsics.into_iter().map(Executive::apply_extrinsic).collect();
+		self.parent_hash = Executive::finalize_block().hash();
+		results
+	}
+
+	/// First block of `period`.
+	fn period_start(period: u64) -> u32 {
+		period as u32 * PeriodDuration::get()
+	}
+}
+
+/// Fee charged for `xt` given the current state.
+fn fee_of(xt: &UncheckedExtrinsic) -> Balance {
+	TransactionPayment::query_info(xt.clone(), xt.encode().len() as u32).partial_fee
+}
+
+fn account(keyring: AccountKeyring) -> AccountId {
+	keyring.to_account_id()
+}
+
+#[test]
+fn membership_proposal_lifecycle() {
+	new_test_ext().execute_with(|| {
+		let mut chain = Chain::new();
+		let alice = account(AccountKeyring::Alice);
+		let bob = account(AccountKeyring::Bob);
+		let charlie = account(AccountKeyring::Charlie);
+
+		// Bob asks for 10 shares in exchange for tribute, Alice sponsors it.
+		let submit = sign(
+			Call::TemplateModule(template::Call::submit_proposal(0, bob.clone(), 10, 0, TRIBUTE, 0, b"hello".to_vec())),
+			AccountKeyring::Bob,
+			0,
+		);
+		let sponsor = sign(Call::TemplateModule(template::Call::sponsor_proposal(0, 0)), AccountKeyring::Alice, 0);
+		let (submit_fee, sponsor_fee) = (fee_of(&submit), fee_of(&sponsor));
+		assert!(submit_fee > 0 && sponsor_fee > 0);
+
+		let results = chain.block(1, vec![submit, sponsor]);
+		assert!(results.iter().all(|r| r == &Ok(Ok(()))), "{:?}", results);
+		assert_eq!(Balances::free_balance(&bob), ENDOWMENT - TRIBUTE - submit_fee);
+		assert_eq!(Balances::reserved_balance(&alice), ProposalDeposit::get());
+		assert_eq!(Balances::free_balance(&alice), ENDOWMENT - ProposalDeposit::get() - sponsor_fee);
+		assert_eq!(Dao::escrow_balance(0), TRIBUTE);
+		// Without a block author, all fees are paid into the guild bank.
+		assert_eq!(Dao::guild_balance(0), submit_fee + sponsor_fee);
+
+		// Voting opens in the period after sponsorship.
+		let starting_period = Dao::proposals(0, 0).unwrap().starting_period;
+		let vote = sign(Call::TemplateModule(template::Call::submit_vote(0, 0, template::Vote::Yes)), AccountKeyring::Alice, 1);
+		let alice_before = Balances::free_balance(&alice);
+		let results = chain.block(Chain::period_start(starting_period), vec![vote]);
+		assert_eq!(results, vec![Ok(Ok(()))]);
+		assert_eq!(Dao::proposals(0, 0).unwrap().yes_votes, 1);
+		// Members' votes are refunded their fee.
+		assert_eq!(Balances::free_balance(&alice), alice_before);
+
+		// Anyone can process once the voting and grace periods are over.
+		let process = sign(Call::TemplateModule(template::Call::process_proposal(0, 0)), AccountKeyring::Charlie, 0);
+		let process_fee = fee_of(&process);
+		let ready = starting_period + VotingPeriodLength::get() + GracePeriodLength::get();
+		let results = chain.block(Chain::period_start(ready), vec![process]);
+		assert_eq!(results, vec![Ok(Ok(()))]);
+
+		assert_eq!(Dao::members(0, &bob).map(|m| m.shares), Some(10));
+		assert_eq!(Dao::total_shares(0), 11);
+		assert_eq!(Balances::reserved_balance(&alice), 0);
+		// Processing is weighed for the heaviest action, and membership proposals are refunded
+		// most of it.
+		let paid = ENDOWMENT + ProcessingReward::get() - Balances::free_balance(&charlie);
+		assert!(0 < paid && paid < process_fee);
+		assert_eq!(Dao::guild_balance(0), TRIBUTE + submit_fee + sponsor_fee + paid);
+
+		// Bob leaves with his fair share, which includes part of the fees, and withdraws it.
+		let ragequit = sign(Call::TemplateModule(template::Call::ragequit(0, 10, 0)), AccountKeyring::Bob, 1);
+		let results = chain.block(Chain::period_start(ready) + 1, vec![ragequit]);
+		assert_eq!(results, vec![Ok(Ok(()))]);
+		assert_eq!(Dao::members(0, &bob).map(|m| m.shares), Some(0));
+		let fair_share = Dao::user_balance(0, &bob);
+		assert!(fair_share > TRIBUTE * 10 / 11);
+
+		let withdraw = sign(Call::TemplateModule(template::Call::withdraw_balance(0, fair_share)), AccountKeyring::Bob, 2);
+		let bob_before = Balances::free_balance(&bob);
+		let withdraw_fee = fee_of(&withdraw);
+		let results = chain.block(Chain::period_start(ready) + 2, vec![withdraw]);
+		assert_eq!(results, vec![Ok(Ok(()))]);
+		assert_eq!(Dao::user_balance(0, &bob), 0);
+		assert_eq!(Balances::free_balance(&bob), bob_before + fair_share - withdraw_fee);
+		assert_eq!(Dao::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn failed_dispatch_still_pays_fee() {
+	new_test_ext().execute_with(|| {
+		let mut chain = Chain::new();
+		let bob = account(AccountKeyring::Bob);
+
+		// Bob is not a member, so cannot sponsor.
+		let submit = sign(
+			Call::TemplateModule(template::Call::submit_proposal(0, bob.clone(), 1, 0, 0, 0, vec![])),
+			AccountKeyring::Bob,
+			0,
+		);
+		let sponsor = sign(Call::TemplateModule(template::Call::sponsor_proposal(0, 0)), AccountKeyring::Bob, 1);
+		let fees = fee_of(&submit) + fee_of(&sponsor);
+		let results = chain.block(1, vec![submit, sponsor]);
+
+		assert_eq!(results[0], Ok(Ok(())));
+		assert!(matches!(results[1], Ok(Err(DispatchError::Module { .. }))), "{:?}", results[1]);
+		assert_eq!(Balances::free_balance(&bob), ENDOWMENT - fees);
+		assert_eq!(Dao::proposal_queue_length(0), 0);
+	});
+}
+
+#[test]
+fn fees_are_split_between_author_and_guild() {
+	new_test_ext().execute_with(|| {
+		let mut chain = Chain::new();
+		let alice = account(AccountKeyring::Alice);
+		let transfer = sign(
+			Call::Balances(BalancesCall::transfer(account(AccountKeyring::Bob), 1_000)),
+			AccountKeyring::Charlie,
+			0,
+		);
+		let fee = fee_of(&transfer);
+
+		// Alice is the only authority, so she authors every slot.
+		let results = chain.authored_block(1, vec![transfer]);
+		assert_eq!(results, vec![Ok(Ok(()))]);
+
+		let author_fee = AuthorFeeShare::get() * fee;
+		assert!(author_fee > 0);
+		assert_eq!(Balances::free_balance(&alice), ENDOWMENT + author_fee);
+		assert_eq!(Dao::guild_balance(0), fee - author_fee);
+		assert_eq!(Dao::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn author_fees_are_paid_to_validator_account() {
+	// Alice validates with keys derived from Dave's seed, who has no account of his own.
+	test_ext_with_session_keys(session_keys(AccountKeyring::Dave)).execute_with(|| {
+		let mut chain = Chain::new();
+		let alice = account(AccountKeyring::Alice);
+		let dave = account(AccountKeyring::Dave);
+		let transfer = sign(
+			Call::Balances(BalancesCall::transfer(account(AccountKeyring::Bob), 1_000)),
+			AccountKeyring::Charlie,
+			0,
+		);
+		let fee = fee_of(&transfer);
+
+		let results = chain.authored_block(1, vec![transfer]);
+		assert_eq!(results, vec![Ok(Ok(()))]);
+
+		assert_eq!(Aura::authorities(), vec![session_keys(AccountKeyring::Dave).aura]);
+		assert_eq!(Balances::free_balance(&alice), ENDOWMENT + AuthorFeeShare::get() * fee);
+		assert_eq!(Balances::free_balance(&dave), 0);
+		assert_eq!(Dao::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn dust_is_paid_into_guild() {
+	new_test_ext().execute_with(|| {
+		let mut chain = Chain::new();
+		let charlie = account(AccountKeyring::Charlie);
+		let transfer = |amount| sign(
+			Call::Balances(BalancesCall::transfer(account(AccountKeyring::Bob), amount)),
+			AccountKeyring::Charlie,
+			0,
+		);
+		// Leave Charlie with less than the existential deposit after paying the fee.
+		let dust = ExistentialDeposit::get() - 1;
+		let fee = fee_of(&transfer(ENDOWMENT));
+		let results = chain.block(1, vec![transfer(ENDOWMENT - fee - dust)]);
+		assert_eq!(results, vec![Ok(Ok(()))]);
+
+		assert_eq!(Balances::free_balance(&charlie), 0);
+		assert_eq!(Dao::guild_balance(0), fee + dust);
+		assert_eq!(Dao::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn votes_from_non_members_are_rejected() {
+	new_test_ext().execute_with(|| {
+		let mut chain = Chain::new();
+		let submit = sign(
+			Call::TemplateModule(template::Call::submit_proposal(0, account(AccountKeyring::Bob), 1, 0, 0, 0, vec![])),
+			AccountKeyring::Bob,
+			0,
+		);
+		let sponsor = sign(Call::TemplateModule(template::Call::sponsor_proposal(0, 0)), AccountKeyring::Alice, 0);
+		chain.block(1, vec![submit, sponsor]);
+
+		let vote = Call::TemplateModule(template::Call::submit_vote(0, 0, template::Vote::No));
+		let bob_before = Balances::free_balance(account(AccountKeyring::Bob));
+		let results = chain.block(Chain::period_start(1), vec![
+			sign(vote.clone(), AccountKeyring::Bob, 1),
+			sign(vote, AccountKeyring::Alice, 1),
+		]);
+
+		assert!(
+			matches!(results[0], Err(TransactionValidityError::Invalid(InvalidTransaction::Custom(_)))),
+			"{:?}",
+			results[0],
+		);
+		assert_eq!(results[1], Ok(Ok(())));
+		assert_eq!(Balances::free_balance(account(AccountKeyring::Bob)), bob_before);
+		assert_eq!(Dao::proposals(0, 0).unwrap().no_votes, 1);
+	});
+}
+
+#[test]
+fn signed_extra_rejects_invalid_transactions() {
+	new_test_ext().execute_with(|| {
+		let mut chain = Chain::new();
+		let call = Call::TemplateModule(template::Call::sponsor_proposal(0, 0));
+
+		// Nonces must match the account's.
+		let results = chain.block(1, vec![
+			sign(call.clone(), AccountKeyring::Alice, 1),
+			sign(call.clone(), AccountKeyring::Alice, 0),
+		]);
+		assert_eq!(results[0], Err(TransactionValidityError::Invalid(InvalidTransaction::Future)));
+		assert!(results[1].is_ok());
+		let results = chain.block(2, vec![sign(call.clone(), AccountKeyring::Alice, 0)]);
+		assert_eq!(results[0], Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)));
+
+		// Accounts that cannot pay fees are rejected before dispatch.
+		let results = chain.block(3, vec![sign(call.clone(), AccountKeyring::Dave, 0)]);
+		assert_eq!(results[0], Err(TransactionValidityError::Invalid(InvalidTransaction::Payment)));
+
+		// Signatures must cover the call.
+		let mut forged = sign(call, AccountKeyring::Alice, 1);
+		forged.function = Call::TemplateModule(template::Call::ragequit(0, 1, 0));
+		let results = chain.block(4, vec![forged]);
+		assert_eq!(results[0], Err(TransactionValidityError::Invalid(InvalidTransaction::BadProof)));
+
+		assert_eq!(System::account_nonce(&account(AccountKeyring::Alice)), 1);
+	});
+}
+
+#[test]
+fn dao_adds_validator_through_action_proposal() {
+	new_test_ext().execute_with(|| {
+		let mut chain = Chain::new();
+		let alice = account(AccountKeyring::Alice);
+		let bob = account(AccountKeyring::Bob);
+
+		// Bob registers his session keys and Alice proposes him as a validator.
+		let set_keys = sign(
+			Call::Session(session::Call::set_keys(session_keys(AccountKeyring::Bob), vec![])),
+			AccountKeyring::Bob,
+			0,
+		);
+		let action = Call::ValidatorSet(validator_set::Call::add_validator(bob.clone()));
+		let submit = sign(
+			Call::TemplateModule(template::Call::submit_action_proposal(0, Box::new(action), vec![])),
+			AccountKeyring::Alice,
+			0,
+		);
+		let sponsor = sign(Call::TemplateModule(template::Call::sponsor_proposal(0, 0)), AccountKeyring::Alice, 1);
+		let results = chain.block(1, vec![set_keys, submit, sponsor]);
+		assert!(results.iter().all(|r| r == &Ok(Ok(()))), "{:?}", results);
+
+		let starting_period = Dao::proposals(0, 0).unwrap().starting_period;
+		let vote = sign(Call::TemplateModule(template::Call::submit_vote(0, 0, template::Vote::Yes)), AccountKeyring::Alice, 2);
+		let results = chain.block(Chain::period_start(starting_period), vec![vote]);
+		assert_eq!(results, vec![Ok(Ok(()))]);
+
+		let process = sign(Call::TemplateModule(template::Call::process_proposal(0, 0)), AccountKeyring::Charlie, 0);
+		let ready = Chain::period_start(starting_period + VotingPeriodLength::get() + GracePeriodLength::get());
+		let results = chain.block(ready, vec![process]);
+		assert_eq!(results, vec![Ok(Ok(()))]);
+		assert_eq!(ValidatorSet::validators(), vec![alice.clone(), bob.clone()]);
+		assert_eq!(Session::validators(), vec![alice.clone()]);
+
+		// The new set is queued at the next session boundary and active at the one after.
+		chain.block(ready + SessionPeriod::get(), vec![]);
+		assert_eq!(Session::validators(), vec![alice.clone()]);
+		chain.block(ready + 2 * SessionPeriod::get(), vec![]);
+		assert_eq!(Session::validators(), vec![alice, bob]);
+		assert_eq!(Aura::authorities().len(), 2);
+		assert_eq!(Dao::do_try_state(), Ok(()));
+	});
+}
+
+#[test]
+fn grandpa_key_ownership_is_provable() {
+	new_test_ext().execute_with(|| {
+		let mut chain = Chain::new();
+		chain.block(1, vec![]);
+		let key = |keyring| (grandpa::fg_primitives::KEY_TYPE, session_keys(keyring).grandpa);
+
+		// Alice's GRANDPA key belongs to a validator of the current session, Bob's does not.
+		let proof = Historical::prove(key(AccountKeyring::Alice)).expect("Alice is a validator; qed");
+		assert!(Historical::prove(key(AccountKeyring::Bob)).is_none());
+
+		// The proof identifies the account to report for an equivocation.
+		assert_eq!(
+			Historical::check_proof(key(AccountKeyring::Alice), proof.clone()),
+			Some((account(AccountKeyring::Alice), ())),
+		);
+		assert_eq!(Historical::check_proof(key(AccountKeyring::Bob), proof), None);
+	});
+}